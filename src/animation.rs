@@ -1,4 +1,4 @@
-use crate::state::GameState;
+use crate::{loading_screen::LoadingAssets, state::GameState};
 use bevy::{prelude::*, utils::HashMap};
 
 pub struct AnimationPlugin;
@@ -28,10 +28,12 @@ fn setup(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
     mut texture_atlases: ResMut<Assets<TextureAtlas>>,
+    mut loading_assets: ResMut<LoadingAssets>,
 ) {
     let mut animations = HashMap::new();
     // twinkle_star
     let texture_handle = asset_server.load("atlas/twinkle_star.png");
+    loading_assets.add(&texture_handle);
     let texture_atlas =
         TextureAtlas::from_grid(texture_handle, Vec2::new(16.0, 16.0), 2, 2, None, None);
     let texture_atlas_handle = texture_atlases.add(texture_atlas);
@@ -50,6 +52,7 @@ fn setup(
 
     // despawn_star
     let texture_handle = asset_server.load("atlas/despawn_star.png");
+    loading_assets.add(&texture_handle);
     let texture_atlas =
         TextureAtlas::from_grid(texture_handle, Vec2::new(32.0, 32.0), 2, 2, None, None);
     let texture_atlas_handle = texture_atlases.add(texture_atlas);
@@ -68,6 +71,7 @@ fn setup(
 
     // despawn_door
     let texture_handle = asset_server.load("atlas/despawn_door.png");
+    loading_assets.add(&texture_handle);
     let texture_atlas =
         TextureAtlas::from_grid(texture_handle, Vec2::new(32.0, 32.0), 2, 2, None, None);
     let texture_atlas_handle = texture_atlases.add(texture_atlas);
@@ -0,0 +1,162 @@
+use crate::{components::*, room::Rooms};
+use bevy::{prelude::*, render::camera::ScalingMode};
+use bevy_ecs_ldtk::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+const MIN_VIEW_SIZE: f32 = 360.0;
+const MAX_VIEW_SIZE: f32 = 960.0;
+const FRAMING_PADDING: f32 = 96.0;
+const CAMERA_DAMPING: f32 = 6.0;
+const DEAD_ZONE_HALF_EXTENTS: Vec2 = Vec2::new(32.0, 48.0);
+const LOOK_AHEAD_DISTANCE: f32 = 64.0;
+const LOOK_AHEAD_DAMPING: f32 = 2.0;
+const LOOK_AHEAD_MIN_SPEED: f32 = 10.0;
+
+pub struct CameraPlugin;
+
+impl Plugin for CameraPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Startup, setup)
+            .add_systems(Update, camera_follow_players);
+    }
+}
+
+fn setup(mut commands: Commands) {
+    let camera = Camera2dBundle::default();
+    commands.spawn((camera, FollowCamera::default()));
+}
+
+/// Where a scrolling camera is looking, kept apart from the damped transform.
+#[derive(Component, Default)]
+struct FollowCamera {
+    level_iid: Option<String>,
+    focus: Vec2,
+    look_ahead: f32,
+}
+
+/// Levels that fit on screen are shown whole; larger ones scroll to frame every live player.
+#[allow(clippy::type_complexity)]
+fn camera_follow_players(
+    mut camera_query: Query<(
+        &mut OrthographicProjection,
+        &mut Transform,
+        &mut FollowCamera,
+    )>,
+    level_query: Query<(&Transform, &Handle<LdtkLevel>), Without<OrthographicProjection>>,
+    player_query: Query<(&GlobalTransform, &Velocity), With<Player>>,
+    level_selection: Res<LevelSelection>,
+    ldtk_levels: Res<Assets<LdtkLevel>>,
+    rooms: Res<Rooms>,
+    time: Res<Time>,
+) {
+    let (mut orthographic_projection, mut camera_transform, mut follow) = camera_query.single_mut();
+
+    for (level_transform, level_handle) in &level_query {
+        let Some(ldtk_level) = ldtk_levels.get(level_handle) else {
+            continue;
+        };
+        let level = &ldtk_level.level;
+        if !level_selection.is_match(&0, level) {
+            continue;
+        }
+
+        let level_min = level_transform.translation.truncate();
+        let level_size = Vec2::new(level.px_wid as f32, level.px_hei as f32);
+        let entered_level = follow.level_iid.as_ref() != Some(&level.iid);
+
+        if level_size.max_element() <= MAX_VIEW_SIZE {
+            let view_size = level_size.max_element().max(MIN_VIEW_SIZE);
+            orthographic_projection.scaling_mode = ScalingMode::Fixed {
+                width: view_size,
+                height: view_size,
+            };
+            let mut center = level_min + level_size / 2.;
+            // pan between connected rooms instead of cutting
+            if rooms.is_active() && !entered_level {
+                let t = 1. - (-CAMERA_DAMPING * time.delta_seconds()).exp();
+                center = camera_transform.translation.truncate().lerp(center, t);
+            }
+            camera_transform.translation.x = center.x;
+            camera_transform.translation.y = center.y;
+            follow.level_iid = Some(level.iid.clone());
+            continue;
+        }
+
+        let Some((players_min, players_max)) = player_query
+            .iter()
+            .map(|(transform, _)| transform.translation().truncate())
+            .fold(None, |bounds: Option<(Vec2, Vec2)>, p| match bounds {
+                Some((min, max)) => Some((min.min(p), max.max(p))),
+                None => Some((p, p)),
+            })
+        else {
+            continue;
+        };
+        follow.level_iid = Some(level.iid.clone());
+
+        let target_size = ((players_max - players_min).max_element() + FRAMING_PADDING * 2.)
+            .clamp(MIN_VIEW_SIZE, MAX_VIEW_SIZE);
+        let players_center = (players_min + players_max) / 2.;
+
+        // the focus only moves once the players push against the edge of the dead zone
+        if entered_level {
+            follow.focus = players_center;
+            follow.look_ahead = 0.;
+        } else {
+            let offset = players_center - follow.focus;
+            follow.focus += offset - offset.clamp(-DEAD_ZONE_HALF_EXTENTS, DEAD_ZONE_HALF_EXTENTS);
+        }
+
+        let average_speed = player_query.iter().map(|(_, v)| v.linvel.x).sum::<f32>()
+            / player_query.iter().len() as f32;
+        let look_ahead_target = if average_speed.abs() > LOOK_AHEAD_MIN_SPEED {
+            average_speed.signum() * LOOK_AHEAD_DISTANCE
+        } else {
+            follow.look_ahead
+        };
+        let look_ahead_t = 1. - (-LOOK_AHEAD_DAMPING * time.delta_seconds()).exp();
+        follow.look_ahead += (look_ahead_target - follow.look_ahead) * look_ahead_t;
+
+        let target_center = follow.focus + Vec2::new(follow.look_ahead, 0.);
+
+        let (view_size, center) = if entered_level {
+            (target_size, target_center)
+        } else {
+            let current_size = match orthographic_projection.scaling_mode {
+                ScalingMode::Fixed { width, .. } => width,
+                _ => target_size,
+            };
+            let t = 1. - (-CAMERA_DAMPING * time.delta_seconds()).exp();
+            (
+                current_size + (target_size - current_size) * t,
+                camera_transform
+                    .translation
+                    .truncate()
+                    .lerp(target_center, t),
+            )
+        };
+        let center = clamp_to_level(center, view_size, level_min, level_size);
+
+        orthographic_projection.scaling_mode = ScalingMode::Fixed {
+            width: view_size,
+            height: view_size,
+        };
+        camera_transform.translation.x = center.x;
+        camera_transform.translation.y = center.y;
+    }
+}
+
+fn clamp_to_level(center: Vec2, view_size: f32, level_min: Vec2, level_size: Vec2) -> Vec2 {
+    let half_view = view_size / 2.;
+    let clamp_axis = |c: f32, min: f32, size: f32| {
+        if size <= view_size {
+            min + size / 2.
+        } else {
+            c.clamp(min + half_view, min + size - half_view)
+        }
+    };
+    Vec2::new(
+        clamp_axis(center.x, level_min.x, level_size.x),
+        clamp_axis(center.y, level_min.y, level_size.y),
+    )
+}
@@ -0,0 +1,212 @@
+use crate::{
+    components::*,
+    key::{give_key, CarriedBy, CarryingKey},
+    player::{player_clone_bundle, store_maximum_split_in_level, MaximumSplit, PlayerTexture},
+    room::Rooms,
+    sfx::{AudioControler, SfxHandles},
+    state::GameState,
+    switch::{Open, Pushed, Restored},
+};
+use bevy::{prelude::*, utils::HashSet};
+use bevy_ecs_ldtk::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+pub struct CheckpointPlugin;
+
+impl Plugin for CheckpointPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Checkpoints>()
+            .add_systems(Update, save_checkpoint.run_if(in_state(GameState::Play)))
+            .add_systems(
+                OnEnter(GameState::Play),
+                restore_checkpoint.after(store_maximum_split_in_level),
+            )
+            .add_systems(OnEnter(GameState::LevelClear), forget_checkpoint)
+            .add_systems(OnEnter(GameState::Title), forget_checkpoint);
+    }
+}
+
+/// Everything needed to put a level back the way it was when a checkpoint was touched.
+pub struct CheckpointSnapshot {
+    checkpoint: String,
+    level_selection: LevelSelection,
+    /// Each player with the iid of the key it carries.
+    players: Vec<(Transform, u8, Option<String>)>,
+    maximum_split: MaximumSplit,
+    remaining_stars: HashSet<String>,
+    remaining_keys: HashSet<String>,
+    closed_doors: HashSet<String>,
+    opened_locks: HashSet<String>,
+    pushed_switches: HashSet<String>,
+    rooms: Rooms,
+}
+
+impl CheckpointSnapshot {
+    pub fn level_selection(&self) -> &LevelSelection {
+        &self.level_selection
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct Checkpoints {
+    pub saved: Option<CheckpointSnapshot>,
+    /// Set by `restart_level` when the next respawn should restore `saved`.
+    pub restoring: bool,
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn save_checkpoint(
+    mut commands: Commands,
+    mut checkpoints: ResMut<Checkpoints>,
+    mut collisions: EventReader<CollisionEvent>,
+    checkpoint_query: Query<&EntityIid, With<Checkpoint>>,
+    player_query: Query<(&GlobalTransform, &Player, Option<&CarryingKey>)>,
+    star_query: Query<&EntityIid, With<Star>>,
+    key_query: Query<&EntityIid, With<Key>>,
+    door_query: Query<&Door, (Without<Open>, Without<Lock>)>,
+    lock_query: Query<&Door, (With<Lock>, With<Open>)>,
+    switch_query: Query<
+        &EntityIid,
+        (
+            With<Switch>,
+            With<Pushed>,
+            Without<PressurePlate>,
+            Without<SwitchDuration>,
+        ),
+    >,
+    maximum_split: Option<Res<MaximumSplit>>,
+    level_selection: Res<LevelSelection>,
+    rooms: Res<Rooms>,
+    sfxs: Res<SfxHandles>,
+) {
+    let Some(maximum_split) = maximum_split else {
+        return;
+    };
+
+    for collision_event in collisions.iter() {
+        let CollisionEvent::Started(e1, e2, _) = collision_event else {
+            continue;
+        };
+        let checkpoint = if player_query.contains(*e1) {
+            checkpoint_query.get(*e2)
+        } else if player_query.contains(*e2) {
+            checkpoint_query.get(*e1)
+        } else {
+            continue;
+        };
+        let Ok(checkpoint) = checkpoint else {
+            continue;
+        };
+
+        let saved_checkpoint = checkpoints
+            .saved
+            .as_ref()
+            .map(|saved| saved.checkpoint.as_str());
+        let is_new = saved_checkpoint != Some(checkpoint.as_str());
+        if is_new {
+            commands.play_sfx(sfxs.switch.clone());
+        }
+
+        checkpoints.saved = Some(CheckpointSnapshot {
+            checkpoint: checkpoint.to_string(),
+            level_selection: level_selection.clone(),
+            players: player_query
+                .iter()
+                .map(|(transform, player, carrying_key)| {
+                    let key = carrying_key
+                        .and_then(|carrying_key| key_query.get(carrying_key.0).ok())
+                        .map(|iid| iid.to_string());
+                    (transform.compute_transform(), player.level, key)
+                })
+                .collect(),
+            maximum_split: *maximum_split,
+            remaining_stars: star_query.iter().map(|iid| iid.to_string()).collect(),
+            remaining_keys: key_query.iter().map(|iid| iid.to_string()).collect(),
+            closed_doors: door_query.iter().map(|door| door.0.clone()).collect(),
+            opened_locks: lock_query.iter().map(|lock| lock.0.clone()).collect(),
+            pushed_switches: switch_query.iter().map(|iid| iid.to_string()).collect(),
+            rooms: rooms.clone(),
+        });
+    }
+}
+
+#[allow(clippy::too_many_arguments, clippy::type_complexity)]
+fn restore_checkpoint(
+    mut commands: Commands,
+    mut checkpoints: ResMut<Checkpoints>,
+    player_query: Query<Entity, With<Player>>,
+    star_query: Query<(Entity, &EntityIid), With<Star>>,
+    key_query: Query<(Entity, &EntityIid), (With<Key>, Without<CarriedBy>)>,
+    door_query: Query<(Entity, &Door, Option<&Lock>)>,
+    switch_query: Query<(Entity, &EntityIid), With<Switch>>,
+    carried_key_query: Query<Entity, With<CarriedBy>>,
+    ldtk_query: Query<Entity, With<Handle<LdtkAsset>>>,
+    player_texture: Res<PlayerTexture>,
+    mut rooms: ResMut<Rooms>,
+) {
+    if !checkpoints.restoring {
+        return;
+    }
+    checkpoints.restoring = false;
+    let Some(snapshot) = checkpoints.saved.as_ref() else {
+        return;
+    };
+
+    for (e, iid) in &star_query {
+        if !snapshot.remaining_stars.contains(iid.as_str()) {
+            commands.entity(e).despawn_recursive();
+        }
+    }
+    for (e, iid) in &key_query {
+        if !snapshot.remaining_keys.contains(iid.as_str()) {
+            commands.entity(e).despawn_recursive();
+        }
+    }
+    // restored doors settle without the sounds and animations of being opened by play
+    for (e, door, lock) in &door_query {
+        let open = if lock.is_some() {
+            snapshot.opened_locks.contains(&door.0)
+        } else {
+            !snapshot.closed_doors.contains(&door.0)
+        };
+        if open {
+            commands.entity(e).insert((Open, Restored));
+        } else {
+            commands.entity(e).insert(Restored);
+        }
+    }
+    for (e, iid) in &switch_query {
+        if snapshot.pushed_switches.contains(iid.as_str()) {
+            commands.entity(e).insert(Pushed);
+        }
+    }
+
+    for e in player_query.iter().chain(&carried_key_query) {
+        commands.entity(e).despawn_recursive();
+    }
+    let Ok(ldtk_entity) = ldtk_query.get_single() else {
+        return;
+    };
+    for (transform, level, key) in &snapshot.players {
+        let player = commands
+            .spawn((
+                player_clone_bundle(&player_texture, *level, *transform, Vec2::ZERO),
+                Artificial,
+            ))
+            .id();
+        commands.entity(ldtk_entity).add_child(player);
+        if let Some((key, _)) = key_query
+            .iter()
+            .find(|(_, iid)| Some(iid.as_str()) == key.as_deref())
+        {
+            give_key(&mut commands, player, key, Some(ldtk_entity));
+        }
+    }
+
+    commands.insert_resource(snapshot.maximum_split);
+    *rooms = snapshot.rooms.clone();
+}
+
+fn forget_checkpoint(mut checkpoints: ResMut<Checkpoints>) {
+    *checkpoints = Checkpoints::default();
+}
@@ -1,10 +1,57 @@
 use bevy::prelude::*;
-use bevy_ecs_ldtk::prelude::*;
+use bevy_ecs_ldtk::{prelude::*, utils::ldtk_grid_coords_to_translation};
 use bevy_rapier2d::prelude::*;
 use std::collections::HashSet;
 #[derive(Clone, Default, Component)]
 pub struct GroundDetection {
     pub on_ground: bool,
+    pub material: TerrainMaterial,
+}
+
+/// What a wall is made of, which changes how players move on it.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default, Hash, Component)]
+pub enum TerrainMaterial {
+    #[default]
+    Normal,
+    /// Slippery; players keep their momentum.
+    Ice,
+    /// Slows players down and keeps them from jumping.
+    Sticky,
+    /// Bounces players back up.
+    Bouncy,
+    /// Carries players along, unless a conveyor switch has reversed the belts.
+    ConveyorLeft,
+    ConveyorRight,
+}
+
+impl TerrainMaterial {
+    pub fn collider_bundle(self) -> (Friction, Restitution, TerrainMaterial) {
+        let (friction, restitution) = match self {
+            TerrainMaterial::Normal
+            | TerrainMaterial::Sticky
+            | TerrainMaterial::ConveyorLeft
+            | TerrainMaterial::ConveyorRight => (1.0, 0.0),
+            TerrainMaterial::Ice => (0.0, 0.0),
+            TerrainMaterial::Bouncy => (1.0, 1.0),
+        };
+        (
+            Friction::new(friction),
+            Restitution {
+                coefficient: restitution,
+                combine_rule: CoefficientCombineRule::Max,
+            },
+            self,
+        )
+    }
+
+    /// Which way a conveyor runs before any reversal: -1 for left, 1 for right, 0 for other ground.
+    pub fn conveyor_direction(self) -> f32 {
+        match self {
+            TerrainMaterial::ConveyorLeft => -1.,
+            TerrainMaterial::ConveyorRight => 1.,
+            _ => 0.,
+        }
+    }
 }
 
 #[derive(Component)]
@@ -18,6 +65,13 @@ pub struct Player {
     pub level: u8,
 }
 
+impl Player {
+    /// Splitting shares the body evenly, so each half weighs half as much.
+    pub fn weight(&self) -> f32 {
+        0.5f32.powi(self.level as i32)
+    }
+}
+
 #[derive(Copy, Clone, Eq, PartialEq, Debug, Default, Component)]
 pub struct Artificial;
 
@@ -27,6 +81,10 @@ pub struct Star;
 #[derive(Copy, Clone, Eq, PartialEq, Debug, Default, Component)]
 pub struct Wall;
 
+/// An IntGrid cell of a jump-through platform.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default, Component)]
+pub struct OneWay;
+
 #[derive(Copy, Clone, Eq, PartialEq, Debug, Default, Component)]
 pub struct UiData;
 
@@ -56,10 +114,145 @@ impl From<&EntityInstance> for UiText {
     }
 }
 
+/// IIDs of every door a switch or logic gate is wired to, from its `Door` entity ref and `Doors` entity ref array.
 #[derive(Clone, PartialEq, Debug, Default, Component)]
-pub struct RelatedDoor(pub String);
+pub struct RelatedDoors(pub Vec<String>);
 
-impl LdtkEntity for RelatedDoor {
+impl From<&EntityInstance> for RelatedDoors {
+    fn from(entity_instance: &EntityInstance) -> Self {
+        let single = entity_instance
+            .get_maybe_entity_ref_field("Door")
+            .ok()
+            .and_then(|entity_ref| entity_ref.as_ref());
+        let many = entity_instance
+            .get_maybe_entity_refs_field("Doors")
+            .map(|entity_refs| entity_refs.iter().flatten())
+            .into_iter()
+            .flatten();
+        RelatedDoors(
+            single
+                .into_iter()
+                .chain(many)
+                .map(|entity_ref| entity_ref.entity_iid.to_string())
+                .collect(),
+        )
+    }
+}
+
+/// Whether a door wired to several switches opens once any of them or all of them are pushed.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default, Component)]
+pub enum SwitchLogic {
+    #[default]
+    Any,
+    All,
+}
+
+impl From<&EntityInstance> for SwitchLogic {
+    fn from(entity_instance: &EntityInstance) -> Self {
+        match entity_instance
+            .get_enum_field("switch_logic")
+            .map(String::as_str)
+        {
+            Ok("All") => SwitchLogic::All,
+            _ => SwitchLogic::Any,
+        }
+    }
+}
+
+/// Whether a switch or logic gate is currently sending its signal on.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default, Component)]
+pub struct Signal(pub bool);
+
+/// How a logic gate combines the signals of its inputs.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default, Component)]
+pub enum LogicGate {
+    #[default]
+    And,
+    Or,
+    Not,
+    Xor,
+    /// Turned on by its first input and off again by its second.
+    Latch,
+}
+
+impl From<&EntityInstance> for LogicGate {
+    fn from(entity_instance: &EntityInstance) -> Self {
+        match entity_instance.get_enum_field("gate").map(String::as_str) {
+            Ok("Or") => LogicGate::Or,
+            Ok("Not") => LogicGate::Not,
+            Ok("Xor") => LogicGate::Xor,
+            Ok("Latch") => LogicGate::Latch,
+            _ => LogicGate::And,
+        }
+    }
+}
+
+/// IIDs of the switches and gates feeding a logic gate, from its `Inputs` entity ref array.
+#[derive(Clone, PartialEq, Eq, Debug, Default, Component)]
+pub struct LogicInputs(pub Vec<String>);
+
+impl From<&EntityInstance> for LogicInputs {
+    fn from(entity_instance: &EntityInstance) -> Self {
+        LogicInputs(
+            entity_instance
+                .get_maybe_entity_refs_field("Inputs")
+                .map(|entity_refs| {
+                    entity_refs
+                        .iter()
+                        .flatten()
+                        .map(|entity_ref| entity_ref.entity_iid.to_string())
+                        .collect()
+                })
+                .unwrap_or_default(),
+        )
+    }
+}
+
+#[derive(Clone, PartialEq, Debug, Default, Component)]
+pub struct Door(pub String);
+
+/// How a door gets out of the way: vanishing on the spot, or sliding along its track as a
+/// kinematic body that carries and shoves players, which also makes it usable as a lift.
+#[derive(Copy, Clone, PartialEq, Debug, Default, Component)]
+pub enum DoorMotion {
+    #[default]
+    Vanish,
+    Slide {
+        offset: Vec2,
+        speed: f32,
+    },
+}
+
+impl From<&EntityInstance> for DoorMotion {
+    fn from(entity_instance: &EntityInstance) -> Self {
+        let width = entity_instance.width as f32;
+        let height = entity_instance.height as f32;
+        let (direction, length) = match entity_instance
+            .get_enum_field("slide_direction")
+            .map(String::as_str)
+        {
+            Ok("Up") => (Vec2::Y, height),
+            Ok("Down") => (Vec2::NEG_Y, height),
+            Ok("Left") => (Vec2::NEG_X, width),
+            Ok("Right") => (Vec2::X, width),
+            _ => return DoorMotion::Vanish,
+        };
+        let distance = entity_instance
+            .get_float_field("slide_distance")
+            .copied()
+            .unwrap_or(length);
+        let speed = entity_instance
+            .get_float_field("slide_speed")
+            .copied()
+            .unwrap_or(48.);
+        DoorMotion::Slide {
+            offset: direction * distance,
+            speed,
+        }
+    }
+}
+
+impl LdtkEntity for Door {
     fn bundle_entity(
         entity_instance: &EntityInstance,
         _: &LayerInstance,
@@ -67,33 +260,206 @@ impl LdtkEntity for RelatedDoor {
         _: Option<&TilesetDefinition>,
         _: &AssetServer,
         _: &mut Assets<TextureAtlas>,
-    ) -> RelatedDoor {
-        let entity_ref = entity_instance
-            .get_entity_ref_field("Door")
-            .expect("Door field not found");
-        RelatedDoor(entity_ref.entity_iid.to_string())
+    ) -> Door {
+        Door(entity_instance.iid.to_string())
     }
 }
 
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default)]
+pub enum PathMode {
+    /// Back and forth along the path.
+    #[default]
+    PingPong,
+    /// From the last point straight back to the first.
+    Loop,
+}
+
+/// A kinematic platform travelling along the points of its LDtk `path` field.
+///
+/// Switches wired to it through their `Door`/`Doors` refs make it move only while they're on.
 #[derive(Clone, PartialEq, Debug, Default, Component)]
-pub struct Door(pub String);
+pub struct MovingPlatform {
+    /// Offsets from where the platform was placed, starting with the placement itself.
+    pub path: Vec<Vec2>,
+    pub speed: f32,
+    pub mode: PathMode,
+    pub origin: Option<Vec3>,
+    pub target: usize,
+    pub forward: bool,
+    /// How far it moved this frame, so the players standing on it can follow.
+    pub step: Vec3,
+}
 
-impl LdtkEntity for Door {
+impl LdtkEntity for MovingPlatform {
     fn bundle_entity(
         entity_instance: &EntityInstance,
-        _: &LayerInstance,
+        layer_instance: &LayerInstance,
         _: Option<&Handle<Image>>,
         _: Option<&TilesetDefinition>,
         _: &AssetServer,
         _: &mut Assets<TextureAtlas>,
-    ) -> Door {
-        Door(entity_instance.iid.to_string())
+    ) -> MovingPlatform {
+        let grid_size = IVec2::splat(layer_instance.grid_size);
+        let to_translation = |coords: IVec2| {
+            ldtk_grid_coords_to_translation(coords, layer_instance.c_hei, grid_size)
+        };
+        let start = to_translation(entity_instance.grid);
+        let path = std::iter::once(Vec2::ZERO)
+            .chain(
+                entity_instance
+                    .get_maybe_points_field("path")
+                    .into_iter()
+                    .flatten()
+                    .flatten()
+                    .map(|point| to_translation(*point) - start),
+            )
+            .collect();
+
+        MovingPlatform {
+            path,
+            speed: entity_instance
+                .get_float_field("speed")
+                .copied()
+                .unwrap_or(48.),
+            mode: match entity_instance
+                .get_enum_field("path_mode")
+                .map(String::as_str)
+            {
+                Ok("Loop") => PathMode::Loop,
+                _ => PathMode::PingPong,
+            },
+            origin: None,
+            target: 1,
+            forward: true,
+            step: Vec3::ZERO,
+        }
     }
 }
 
 #[derive(Clone, PartialEq, Debug, Default, Component)]
 pub struct Switch;
 
+/// A switch that stays pushed only while a player stands on it.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default, Component)]
+pub struct PressurePlate;
+
+/// How many seconds a timed switch keeps its doors open before popping back up.
+#[derive(Copy, Clone, PartialEq, Debug, Default, Component)]
+pub struct SwitchDuration(pub f32);
+
+impl From<&EntityInstance> for SwitchDuration {
+    fn from(entity_instance: &EntityInstance) -> Self {
+        SwitchDuration(
+            entity_instance
+                .get_float_field("duration")
+                .copied()
+                .unwrap_or(5.),
+        )
+    }
+}
+
+/// Whether a switch runs every conveyor belt backwards while it's on, from its `reverse_conveyors` field.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default, Component)]
+pub struct ReversesConveyors(pub bool);
+
+impl From<&EntityInstance> for ReversesConveyors {
+    fn from(entity_instance: &EntityInstance) -> Self {
+        ReversesConveyors(
+            entity_instance
+                .get_bool_field("reverse_conveyors")
+                .copied()
+                .unwrap_or(false),
+        )
+    }
+}
+
+/// Players currently standing on a switch.
+#[derive(Clone, PartialEq, Eq, Debug, Default, Component)]
+pub struct SwitchPressers(pub HashSet<Entity>);
+
+/// How much weight has to stand on a switch to push it; a whole body weighs 1 and each split halves it.
+#[derive(Copy, Clone, PartialEq, Debug, Default, Component)]
+pub struct RequiredWeight(pub f32);
+
+impl From<&EntityInstance> for RequiredWeight {
+    fn from(entity_instance: &EntityInstance) -> Self {
+        RequiredWeight(
+            entity_instance
+                .get_float_field("required_weight")
+                .copied()
+                .unwrap_or(0.),
+        )
+    }
+}
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default, Component)]
+pub struct Checkpoint;
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default, Component)]
+pub struct Key;
+
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default, Component)]
+pub struct Lock;
+
+/// A pad that splits any player touching it, or fuses two players of the same size standing on it.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default, Component)]
+pub struct Pad {
+    pub merger: bool,
+    /// Whether the bodies it creates or removes count against the level's `MaximumSplit`.
+    pub consumes_split: bool,
+}
+
+impl From<&EntityInstance> for Pad {
+    fn from(entity_instance: &EntityInstance) -> Self {
+        Pad {
+            merger: entity_instance.identifier == "Merger_Pad",
+            consumes_split: entity_instance
+                .get_bool_field("consume_split")
+                .copied()
+                .unwrap_or(false),
+        }
+    }
+}
+
+/// Sends players touching it to the teleporter its "Destination" field points at.
+#[derive(Clone, Eq, PartialEq, Debug, Default, Component)]
+pub struct Teleporter {
+    pub destination: Option<String>,
+}
+
+impl From<&EntityInstance> for Teleporter {
+    fn from(entity_instance: &EntityInstance) -> Self {
+        Teleporter {
+            destination: entity_instance
+                .get_maybe_entity_ref_field("Destination")
+                .ok()
+                .and_then(|entity_ref| entity_ref.as_ref())
+                .map(|entity_ref| entity_ref.entity_iid.to_string()),
+        }
+    }
+}
+
+/// A barrier only players whose `Player.level` lies within `min_level..=max_level` can pass.
+#[derive(Copy, Clone, Eq, PartialEq, Debug, Default, Component)]
+pub struct SizeGate {
+    pub min_level: u8,
+    pub max_level: u8,
+}
+
+impl From<&EntityInstance> for SizeGate {
+    fn from(entity_instance: &EntityInstance) -> Self {
+        let level_field = |identifier, default| {
+            entity_instance
+                .get_int_field(identifier)
+                .map_or(default, |level| (*level).clamp(0, u8::MAX as i32) as u8)
+        };
+        SizeGate {
+            min_level: level_field("min_level", 0),
+            max_level: level_field("max_level", u8::MAX),
+        }
+    }
+}
+
 #[derive(Clone, Debug, Default, Bundle, LdtkIntCell)]
 pub struct ColliderBundle {
     pub collider: Collider,
@@ -105,9 +471,39 @@ pub struct ColliderBundle {
     pub density: ColliderMassProperties,
 }
 
+/// Collider size from the entity's LDtk width/height scaled by `scale`, unless the optional
+/// `collider_width`/`collider_height` fields give it in pixels.
+pub(crate) fn collider_size(entity_instance: &EntityInstance, scale: Vec2) -> Vec2 {
+    let size = Vec2::new(entity_instance.width as f32, entity_instance.height as f32) * scale;
+    Vec2::new(
+        entity_instance
+            .get_float_field("collider_width")
+            .copied()
+            .unwrap_or(size.x),
+        entity_instance
+            .get_float_field("collider_height")
+            .copied()
+            .unwrap_or(size.y),
+    )
+}
+
+pub(crate) fn cuboid(size: Vec2) -> Collider {
+    Collider::cuboid(size.x / 2., size.y / 2.)
+}
+
 impl From<&EntityInstance> for ColliderBundle {
     fn from(entity_instance: &EntityInstance) -> ColliderBundle {
         let rotation_constraints = LockedAxes::ROTATION_LOCKED;
+        let solid = |collider| ColliderBundle {
+            collider,
+            rigid_body: RigidBody::Fixed,
+            friction: Friction {
+                coefficient: 1.0,
+                ..Default::default()
+            },
+            rotation_constraints,
+            ..Default::default()
+        };
 
         match entity_instance.identifier.as_ref() {
             "Player" => ColliderBundle {
@@ -120,25 +516,26 @@ impl From<&EntityInstance> for ColliderBundle {
                 rotation_constraints,
                 ..Default::default()
             },
-            "Horizontal_Door" => ColliderBundle {
-                collider: Collider::cuboid(20., 6.), // a bit longer to prevent tunneling
-                rigid_body: RigidBody::Fixed,
-                friction: Friction {
-                    coefficient: 1.0,
-                    ..Default::default()
-                },
-                rotation_constraints,
-                ..Default::default()
-            },
-            "Vertical_Door" => ColliderBundle {
-                collider: Collider::cuboid(6., 20.), // a bit longer to prevent tunneling
-                rigid_body: RigidBody::Fixed,
-                friction: Friction {
-                    coefficient: 1.0,
-                    ..Default::default()
-                },
-                rotation_constraints,
-                ..Default::default()
+            "Horizontal_Door" | "Vertical_Door" => {
+                // thinner than the sprite, and a bit longer to prevent tunneling
+                let horizontal = entity_instance.width >= entity_instance.height;
+                let scale = if horizontal {
+                    Vec2::new(1., 0.75)
+                } else {
+                    Vec2::new(0.75, 1.)
+                };
+                let mut size = collider_size(entity_instance, scale);
+                if horizontal {
+                    size.x += 8.;
+                } else {
+                    size.y += 8.;
+                }
+                solid(cuboid(size))
+            }
+            "Lock" | "Size_Gate" => solid(cuboid(collider_size(entity_instance, Vec2::ONE))),
+            "Moving_Platform" => ColliderBundle {
+                rigid_body: RigidBody::KinematicPositionBased,
+                ..solid(cuboid(collider_size(entity_instance, Vec2::ONE)))
             },
             _ => ColliderBundle::default(),
         }
@@ -155,22 +552,19 @@ pub struct SensorBundle {
 
 impl From<&EntityInstance> for SensorBundle {
     fn from(entity_instance: &EntityInstance) -> SensorBundle {
-        let rotation_constraints = LockedAxes::ROTATION_LOCKED;
+        // how much of the sprite has to be touched to trigger the sensor
+        let scale = match entity_instance.identifier.as_ref() {
+            "Star" | "Switch" => Vec2::splat(0.5),
+            "Pressure_Plate" => Vec2::new(0.75, 0.5),
+            "Teleporter" => Vec2::splat(0.5),
+            _ => Vec2::ONE,
+        };
 
-        match entity_instance.identifier.as_ref() {
-            "Star" => SensorBundle {
-                collider: Collider::cuboid(4., 4.),
-                sensor: Sensor,
-                active_events: ActiveEvents::COLLISION_EVENTS,
-                rotation_constraints,
-            },
-            "Switch" => SensorBundle {
-                collider: Collider::cuboid(4., 4.),
-                sensor: Sensor,
-                active_events: ActiveEvents::COLLISION_EVENTS,
-                rotation_constraints,
-            },
-            _ => unreachable!(),
+        SensorBundle {
+            collider: cuboid(collider_size(entity_instance, scale)),
+            sensor: Sensor,
+            active_events: ActiveEvents::COLLISION_EVENTS,
+            rotation_constraints: LockedAxes::ROTATION_LOCKED,
         }
     }
 }
@@ -202,7 +596,64 @@ pub struct SwitchBundle {
     #[from_entity_instance]
     sensor_bundle: SensorBundle,
     switch: Switch,
-    related_door: RelatedDoor,
+    signal: Signal,
+    pressers: SwitchPressers,
+    #[from_entity_instance]
+    required_weight: RequiredWeight,
+    #[from_entity_instance]
+    reverses_conveyors: ReversesConveyors,
+    #[from_entity_instance]
+    related_doors: RelatedDoors,
+}
+
+#[derive(Clone, Default, Bundle, LdtkEntity)]
+pub struct TimedSwitchBundle {
+    #[sprite_sheet_bundle]
+    pub sprite_bundle: SpriteSheetBundle,
+    #[from_entity_instance]
+    sensor_bundle: SensorBundle,
+    switch: Switch,
+    signal: Signal,
+    pressers: SwitchPressers,
+    #[from_entity_instance]
+    required_weight: RequiredWeight,
+    #[from_entity_instance]
+    reverses_conveyors: ReversesConveyors,
+    #[from_entity_instance]
+    duration: SwitchDuration,
+    #[from_entity_instance]
+    related_doors: RelatedDoors,
+}
+
+#[derive(Clone, Default, Bundle, LdtkEntity)]
+pub struct PressurePlateBundle {
+    #[sprite_sheet_bundle]
+    pub sprite_bundle: SpriteSheetBundle,
+    #[from_entity_instance]
+    sensor_bundle: SensorBundle,
+    switch: Switch,
+    signal: Signal,
+    pressure_plate: PressurePlate,
+    pressers: SwitchPressers,
+    #[from_entity_instance]
+    required_weight: RequiredWeight,
+    #[from_entity_instance]
+    reverses_conveyors: ReversesConveyors,
+    #[from_entity_instance]
+    related_doors: RelatedDoors,
+}
+
+#[derive(Clone, Default, Bundle, LdtkEntity)]
+pub struct LogicGateBundle {
+    #[sprite_sheet_bundle]
+    sprite_bundle: SpriteSheetBundle,
+    #[from_entity_instance]
+    gate: LogicGate,
+    #[from_entity_instance]
+    inputs: LogicInputs,
+    signal: Signal,
+    #[from_entity_instance]
+    related_doors: RelatedDoors,
 }
 
 #[derive(Clone, Default, Bundle, LdtkEntity)]
@@ -211,12 +662,65 @@ pub struct DoorBundle {
     sprite_bundle: SpriteSheetBundle,
     #[from_entity_instance]
     collider_bundle: ColliderBundle,
+    #[ldtk_entity]
     door: Door,
+    #[from_entity_instance]
+    switch_logic: SwitchLogic,
+    #[from_entity_instance]
+    motion: DoorMotion,
 }
 
-#[derive(Clone, Debug, Default, Bundle, LdtkIntCell)]
-pub struct WallBundle {
-    wall: Wall,
+/// A solid platform that rides along its LDtk path, carrying whoever stands on it.
+#[derive(Clone, Default, Bundle, LdtkEntity)]
+pub struct MovingPlatformBundle {
+    #[sprite_sheet_bundle]
+    sprite_bundle: SpriteSheetBundle,
+    #[from_entity_instance]
+    collider_bundle: ColliderBundle,
+    #[ldtk_entity]
+    platform: MovingPlatform,
+}
+
+#[derive(Clone, Default, Bundle, LdtkEntity)]
+pub struct PadBundle {
+    #[sprite_sheet_bundle]
+    sprite_bundle: SpriteSheetBundle,
+    #[from_entity_instance]
+    sensor_bundle: SensorBundle,
+    #[from_entity_instance]
+    pad: Pad,
+}
+
+#[derive(Clone, Default, Bundle, LdtkEntity)]
+pub struct TeleporterBundle {
+    #[sprite_sheet_bundle]
+    sprite_bundle: SpriteSheetBundle,
+    #[from_entity_instance]
+    sensor_bundle: SensorBundle,
+    #[from_entity_instance]
+    teleporter: Teleporter,
+}
+
+#[derive(Clone, Default, Bundle, LdtkEntity)]
+pub struct SizeGateBundle {
+    #[sprite_sheet_bundle]
+    sprite_bundle: SpriteSheetBundle,
+    #[from_entity_instance]
+    collider_bundle: ColliderBundle,
+    #[from_entity_instance]
+    size_gate: SizeGate,
+}
+
+/// A door that opens for a player carrying a key rather than for a switch.
+#[derive(Clone, Default, Bundle, LdtkEntity)]
+pub struct LockBundle {
+    #[sprite_sheet_bundle]
+    sprite_bundle: SpriteSheetBundle,
+    #[from_entity_instance]
+    collider_bundle: ColliderBundle,
+    #[ldtk_entity]
+    door: Door,
+    lock: Lock,
 }
 
 #[derive(Clone, Default, Bundle, LdtkEntity)]
@@ -1,4 +1,4 @@
-pub const LEVEL_IIDS: [&str; 11] = [
+pub const LEVEL_IIDS: [&str; 28] = [
     "e5952f00-8990-11ee-804a-c3ab159448a8",
     "cef761b0-8990-11ee-aa26-3b2a65529256",
     "b9f08450-8990-11ee-804a-3da8fa653cad",
@@ -10,5 +10,22 @@ pub const LEVEL_IIDS: [&str; 11] = [
     "3da33040-8990-11ee-a192-d3c748be6cba",
     "df4aa930-8990-11ee-9562-cb73eff6827e",
     "c1579e80-8990-11ee-a192-61e0f62883a8",
+    "5c69e185-8afb-49c1-83b9-4d86ad938d1f",
+    "65635fbf-17e0-4a28-a47e-8342a36704fe",
+    "8c3082ce-3622-4b56-939b-6ffd97cb9246",
+    "4bab4df0-a299-4de3-a8a9-6d9e0db8113c",
+    "7b8ff1f4-cc57-458a-a34e-f9df119c85c2",
+    "7367267a-9f67-43aa-88ad-864c748a2f0b",
+    "5aea3cae-4b65-4a6d-9155-dcda09503ca0",
+    "b0c306d5-08ba-40e9-9fee-75d435755741",
+    "059fc1f7-b966-43c9-b08f-7fb0c7f5db69",
+    "edb4b87c-09e1-4a7d-adfd-07c0ce258d1e",
+    "927075c4-a01a-4762-9337-1bdab5e3f61a",
+    "9fbf0e4e-c5d7-4116-9d05-2ba5a79ae76d",
+    "43b1fcd1-e414-4e39-9fd1-45aa88d491b8",
+    "67408637-5630-4961-b595-61f036957097",
+    "6c76bc05-b2b5-4bcf-ba8c-3bf9619789f9",
+    "c1742ce8-14ef-4010-89c6-a4c1dec4fba1",
+    "17300704-512e-4e26-824a-5f6184b8e873",
 ];
 pub const WINDOW_SIZE: (f32, f32) = (720.0, 720.0);
@@ -0,0 +1,107 @@
+use crate::{components::*, state::GameState};
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+const CONVEYOR_VELOCITY: f32 = 60.0;
+const STRIPE_SPACING: f32 = 8.0;
+const STRIPE_SIZE: Vec2 = Vec2::new(2.0, 3.0);
+const STRIPE_COLOR: Color = Color::rgb(0.95, 0.75, 0.25);
+
+pub struct ConveyorPlugin;
+
+impl Plugin for ConveyorPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Conveyors>()
+            .add_systems(Update, spawn_conveyor_stripes)
+            .add_systems(
+                Update,
+                (reverse_conveyors, move_conveyor_stripes)
+                    .chain()
+                    .run_if(in_state(GameState::Play)),
+            );
+    }
+}
+
+/// Whether the belts of the current level are running backwards.
+#[derive(Resource, Default)]
+pub struct Conveyors {
+    reversed: bool,
+}
+
+impl Conveyors {
+    /// How fast ground of this material carries whoever stands on it.
+    pub fn velocity(&self, material: TerrainMaterial) -> f32 {
+        let direction = if self.reversed { -1. } else { 1. };
+        material.conveyor_direction() * direction * CONVEYOR_VELOCITY
+    }
+}
+
+fn reverse_conveyors(
+    mut conveyors: ResMut<Conveyors>,
+    switch_query: Query<(&ReversesConveyors, &Signal)>,
+) {
+    let reversed = switch_query
+        .iter()
+        .any(|(reverses, signal)| reverses.0 && signal.0);
+    if conveyors.reversed != reversed {
+        conveyors.reversed = reversed;
+    }
+}
+
+/// Marks on top of a belt that scroll along with it.
+#[derive(Component)]
+struct ConveyorStripe {
+    material: TerrainMaterial,
+    half_width: f32,
+}
+
+fn spawn_conveyor_stripes(
+    mut commands: Commands,
+    belt_query: Query<(Entity, &TerrainMaterial, &Collider), Added<TerrainMaterial>>,
+) {
+    for (e, material, collider) in &belt_query {
+        if material.conveyor_direction() == 0. {
+            continue;
+        }
+        let Some(half_extents) = collider.as_cuboid().map(|c| c.half_extents()) else {
+            continue;
+        };
+        let stripe_count = (half_extents.x * 2. / STRIPE_SPACING) as usize;
+        commands.entity(e).with_children(|belt| {
+            for i in 0..stripe_count {
+                belt.spawn((
+                    SpriteBundle {
+                        sprite: Sprite {
+                            color: STRIPE_COLOR,
+                            custom_size: Some(STRIPE_SIZE),
+                            ..Default::default()
+                        },
+                        transform: Transform::from_xyz(
+                            -half_extents.x + (i as f32 + 0.5) * STRIPE_SPACING,
+                            half_extents.y - STRIPE_SIZE.y / 2.,
+                            1.,
+                        ),
+                        ..Default::default()
+                    },
+                    ConveyorStripe {
+                        material: *material,
+                        half_width: half_extents.x,
+                    },
+                ));
+            }
+        });
+    }
+}
+
+fn move_conveyor_stripes(
+    mut stripe_query: Query<(&mut Transform, &ConveyorStripe)>,
+    conveyors: Res<Conveyors>,
+    time: Res<Time>,
+) {
+    for (mut transform, stripe) in &mut stripe_query {
+        let x = transform.translation.x
+            + conveyors.velocity(stripe.material) * time.delta_seconds()
+            + stripe.half_width;
+        transform.translation.x = x.rem_euclid(stripe.half_width * 2.) - stripe.half_width;
+    }
+}
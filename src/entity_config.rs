@@ -0,0 +1,208 @@
+use crate::{components::*, loading_screen::LoadingAssets, state::GameState};
+use bevy::{
+    asset::{AssetLoader, LoadContext, LoadedAsset},
+    prelude::*,
+    reflect::{TypePath, TypeUuid},
+    utils::{BoxedFuture, HashMap},
+};
+use bevy_ecs_ldtk::prelude::*;
+use bevy_rapier2d::prelude::*;
+use serde::Deserialize;
+
+const ENTITY_CONFIG_PATH: &str = "levels.entities.json";
+
+/// Gives LDtk entities and IntGrid values that aren't registered in `main.rs` their behaviour
+/// from `assets/levels.entities.json`, so common entity types need no code.
+pub struct EntityConfigPlugin;
+
+impl Plugin for EntityConfigPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_asset::<EntityConfig>()
+            .init_asset_loader::<EntityConfigLoader>()
+            .register_default_ldtk_entity::<ConfiguredEntityBundle>()
+            .register_default_ldtk_int_cell::<ConfiguredIntCellBundle>()
+            .add_systems(OnEnter(GameState::Title), load_entity_config)
+            .add_systems(Update, (configure_entities, configure_int_cells));
+    }
+}
+
+/// Behaviours keyed by LDtk entity identifier and by IntGrid value.
+#[derive(Deserialize, TypeUuid, TypePath, Debug, Default)]
+#[uuid = "6f1d0c52-3b8e-4a52-9d7e-2f7f7c3e5a14"]
+pub struct EntityConfig {
+    #[serde(default)]
+    entities: HashMap<String, EntityBehaviour>,
+    #[serde(default)]
+    int_cells: HashMap<i32, IntCellBehaviour>,
+}
+
+/// The sprite always comes from the entity's tile in the editor.
+#[derive(Deserialize, Debug, Clone)]
+#[serde(default)]
+struct EntityBehaviour {
+    collider: ColliderKind,
+    /// Collider size relative to the entity's LDtk width and height.
+    scale: [f32; 2],
+    tags: Vec<EntityTag>,
+}
+
+impl Default for EntityBehaviour {
+    fn default() -> Self {
+        EntityBehaviour {
+            collider: ColliderKind::None,
+            scale: [1., 1.],
+            tags: Vec::new(),
+        }
+    }
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+struct IntCellBehaviour {
+    tags: Vec<EntityTag>,
+}
+
+#[derive(Deserialize, Debug, Clone, Copy, Default)]
+enum ColliderKind {
+    #[default]
+    None,
+    Solid,
+    Sensor,
+}
+
+/// Marker components a configured entity can be given.
+#[derive(Deserialize, Debug, Clone, Copy)]
+enum EntityTag {
+    Bouncy,
+    Checkpoint,
+    ConveyorLeft,
+    ConveyorRight,
+    Ice,
+    Key,
+    OneWay,
+    Star,
+    Sticky,
+    Wall,
+}
+
+impl EntityTag {
+    fn insert(self, entity: &mut bevy::ecs::system::EntityCommands) {
+        match self {
+            EntityTag::Bouncy => entity.insert((Wall, TerrainMaterial::Bouncy)),
+            EntityTag::Checkpoint => entity.insert(Checkpoint),
+            EntityTag::ConveyorLeft => entity.insert((Wall, TerrainMaterial::ConveyorLeft)),
+            EntityTag::ConveyorRight => entity.insert((Wall, TerrainMaterial::ConveyorRight)),
+            EntityTag::Ice => entity.insert((Wall, TerrainMaterial::Ice)),
+            EntityTag::Key => entity.insert(Key),
+            EntityTag::OneWay => entity.insert(OneWay),
+            EntityTag::Star => entity.insert(Star),
+            EntityTag::Sticky => entity.insert((Wall, TerrainMaterial::Sticky)),
+            EntityTag::Wall => entity.insert(Wall),
+        };
+    }
+}
+
+#[derive(Default)]
+struct EntityConfigLoader;
+
+impl AssetLoader for EntityConfigLoader {
+    fn load<'a>(
+        &'a self,
+        bytes: &'a [u8],
+        load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<(), bevy::asset::Error>> {
+        Box::pin(async move {
+            let config: EntityConfig = serde_json::from_slice(bytes)?;
+            load_context.set_default_asset(LoadedAsset::new(config));
+            Ok(())
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["entities.json"]
+    }
+}
+
+#[derive(Resource)]
+struct EntityConfigHandle(Handle<EntityConfig>);
+
+fn load_entity_config(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut loading_assets: ResMut<LoadingAssets>,
+) {
+    let handle = asset_server.load(ENTITY_CONFIG_PATH);
+    loading_assets.add(&handle);
+    commands.insert_resource(EntityConfigHandle(handle));
+}
+
+/// Entities and cells wait here until the config has loaded.
+#[derive(Component, Clone, Default)]
+struct Unconfigured;
+
+#[derive(Clone, Default, Bundle, LdtkEntity)]
+struct ConfiguredEntityBundle {
+    #[sprite_sheet_bundle]
+    sprite_bundle: SpriteSheetBundle,
+    unconfigured: Unconfigured,
+}
+
+#[derive(Clone, Default, Bundle, LdtkIntCell)]
+struct ConfiguredIntCellBundle {
+    unconfigured: Unconfigured,
+}
+
+fn configure_entities(
+    mut commands: Commands,
+    entity_query: Query<(Entity, &EntityInstance), With<Unconfigured>>,
+    config_handle: Option<Res<EntityConfigHandle>>,
+    configs: Res<Assets<EntityConfig>>,
+) {
+    let Some(config) = config_handle.and_then(|handle| configs.get(&handle.0)) else {
+        return;
+    };
+
+    for (e, entity_instance) in &entity_query {
+        let mut entity = commands.entity(e);
+        entity.remove::<Unconfigured>();
+        let Some(behaviour) = config.entities.get(&entity_instance.identifier) else {
+            continue;
+        };
+
+        let collider = cuboid(collider_size(entity_instance, behaviour.scale.into()));
+        match behaviour.collider {
+            ColliderKind::None => (),
+            ColliderKind::Solid => {
+                entity.insert((collider, RigidBody::Fixed, Friction::new(1.0)));
+            }
+            ColliderKind::Sensor => {
+                entity.insert((collider, Sensor, ActiveEvents::COLLISION_EVENTS));
+            }
+        }
+        for tag in &behaviour.tags {
+            tag.insert(&mut entity);
+        }
+    }
+}
+
+fn configure_int_cells(
+    mut commands: Commands,
+    cell_query: Query<(Entity, &IntGridCell), With<Unconfigured>>,
+    config_handle: Option<Res<EntityConfigHandle>>,
+    configs: Res<Assets<EntityConfig>>,
+) {
+    let Some(config) = config_handle.and_then(|handle| configs.get(&handle.0)) else {
+        return;
+    };
+
+    for (e, cell) in &cell_query {
+        let mut entity = commands.entity(e);
+        entity.remove::<Unconfigured>();
+        let Some(behaviour) = config.int_cells.get(&cell.value) else {
+            continue;
+        };
+        for tag in &behaviour.tags {
+            tag.insert(&mut entity);
+        }
+    }
+}
@@ -54,10 +54,17 @@ fn ground_detection(
 fn update_on_ground(
     mut ground_detectors: Query<&mut GroundDetection>,
     ground_sensors: Query<&GroundSensor, Changed<GroundSensor>>,
+    materials: Query<&TerrainMaterial>,
 ) {
     for sensor in &ground_sensors {
         if let Ok(mut ground_detection) = ground_detectors.get_mut(sensor.ground_detection_entity) {
             ground_detection.on_ground = !sensor.intersecting_ground_entities.is_empty();
+            // standing across two materials, the unusual one wins
+            ground_detection.material = materials
+                .iter_many(&sensor.intersecting_ground_entities)
+                .copied()
+                .find(|material| *material != TerrainMaterial::Normal)
+                .unwrap_or_default();
         }
     }
 }
@@ -98,7 +105,7 @@ fn spawn_ground_sensor(
 
 fn spawn_wall_collision(
     mut commands: Commands,
-    wall_query: Query<(&GridCoords, &Parent), Added<Wall>>,
+    wall_query: Query<(&GridCoords, &Parent, Option<&TerrainMaterial>), Added<Wall>>,
     parent_query: Query<&Parent, Without<Wall>>,
     level_query: Query<(Entity, &Handle<LdtkLevel>)>,
     levels: Res<Assets<LdtkLevel>>,
@@ -117,20 +124,30 @@ fn spawn_wall_collision(
         bottom: i32,
     }
 
-    let mut level_to_wall_locations: HashMap<Entity, HashSet<GridCoords>> = HashMap::new();
+    // each material is merged into its own rectangles
+    let mut level_to_wall_locations: HashMap<
+        Entity,
+        HashMap<TerrainMaterial, HashSet<GridCoords>>,
+    > = HashMap::new();
 
-    wall_query.for_each(|(&grid_coords, parent)| {
+    wall_query.for_each(|(&grid_coords, parent, material)| {
         if let Ok(grandparent) = parent_query.get(parent.get()) {
             level_to_wall_locations
                 .entry(grandparent.get())
                 .or_default()
+                .entry(material.copied().unwrap_or_default())
+                .or_default()
                 .insert(grid_coords);
         }
     });
 
     if !wall_query.is_empty() {
         level_query.for_each(|(level_entity, level_handle)| {
-            if let Some(level_walls) = level_to_wall_locations.get(&level_entity) {
+            for (material, level_walls) in level_to_wall_locations
+                .get(&level_entity)
+                .into_iter()
+                .flatten()
+            {
                 let level = levels
                     .get(level_handle)
                     .expect("Level should be loaded by this point");
@@ -218,6 +235,7 @@ fn spawn_wall_collision(
                         }
                         level
                             .spawn_empty()
+                            .insert(material.collider_bundle())
                             .insert(Collider::cuboid(
                                 (wall_rect.right as f32 - wall_rect.left as f32 + 1.)
                                     * grid_size as f32
@@ -227,7 +245,6 @@ fn spawn_wall_collision(
                                     / 2.,
                             ))
                             .insert(RigidBody::Fixed)
-                            .insert(Friction::new(1.0))
                             .insert(Transform::from_xyz(
                                 (wall_rect.left + wall_rect.right + 1) as f32 * grid_size as f32
                                     / 2.,
@@ -0,0 +1,139 @@
+use crate::{
+    components::*,
+    sfx::{AudioControler, SfxHandles},
+    state::GameState,
+    switch::Open,
+};
+use bevy::prelude::*;
+use bevy_ecs_ldtk::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+const KEY_OFFSET: Vec3 = Vec3::new(0.0, 24.0, 1.0);
+const CARRIER_TINT: Color = Color::rgb(1.0, 0.9, 0.5);
+
+pub struct KeyPlugin;
+
+impl Plugin for KeyPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, add_lock_events).add_systems(
+            Update,
+            (pick_up_key, tint_key_carriers, carry_key, unlock_lock)
+                .run_if(in_state(GameState::Play)),
+        );
+    }
+}
+
+/// On a key, the player carrying it.
+#[derive(Component)]
+pub(crate) struct CarriedBy(Entity);
+
+/// On a player, the key it carries.
+#[derive(Component)]
+pub(crate) struct CarryingKey(pub(crate) Entity);
+
+fn add_lock_events(mut commands: Commands, lock_query: Query<Entity, Added<Lock>>) {
+    for e in &lock_query {
+        commands.entity(e).insert(ActiveEvents::COLLISION_EVENTS);
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn pick_up_key(
+    mut commands: Commands,
+    mut collisions: EventReader<CollisionEvent>,
+    player_query: Query<(), (With<Player>, Without<CarryingKey>)>,
+    key_query: Query<Entity, (With<Key>, Without<CarriedBy>)>,
+    ldtk_query: Query<Entity, With<Handle<LdtkAsset>>>,
+) {
+    let mut carriers = Vec::new();
+    for collision_event in collisions.iter() {
+        let CollisionEvent::Started(e1, e2, _) = collision_event else {
+            continue;
+        };
+        let (player, key) = if key_query.contains(*e2) {
+            (*e1, *e2)
+        } else if key_query.contains(*e1) {
+            (*e2, *e1)
+        } else {
+            continue;
+        };
+        if carriers.iter().any(|&(p, k)| p == player || k == key) {
+            continue;
+        }
+        if !player_query.contains(player) {
+            continue;
+        }
+
+        carriers.push((player, key));
+        give_key(&mut commands, player, key, ldtk_query.get_single().ok());
+    }
+}
+
+/// Hands a key to a player, also used to put keys back into hands on a checkpoint restore.
+pub(crate) fn give_key(
+    commands: &mut Commands,
+    player: Entity,
+    key: Entity,
+    ldtk_entity: Option<Entity>,
+) {
+    commands.entity(player).insert(CarryingKey(key));
+    // the key follows players between rooms, so it lives on the world like clones do
+    commands.entity(key).insert((CarriedBy(player), Artificial));
+    if let Some(ldtk_entity) = ldtk_entity {
+        commands.entity(ldtk_entity).add_child(key);
+    }
+}
+
+fn tint_key_carriers(mut player_query: Query<&mut TextureAtlasSprite, Added<CarryingKey>>) {
+    for mut sprite in &mut player_query {
+        sprite.color = CARRIER_TINT;
+    }
+}
+
+fn carry_key(
+    mut commands: Commands,
+    mut key_query: Query<(Entity, &CarriedBy, &mut Transform), With<Key>>,
+    player_query: Query<&GlobalTransform, With<Player>>,
+) {
+    for (key, carried_by, mut transform) in &mut key_query {
+        match player_query.get(carried_by.0) {
+            Ok(player_transform) => {
+                transform.translation = player_transform.translation() + KEY_OFFSET;
+            }
+            // the carrier was consumed by a star or split, so the key drops where it was
+            Err(_) => {
+                commands.entity(key).remove::<CarriedBy>();
+            }
+        }
+    }
+}
+
+fn unlock_lock(
+    mut commands: Commands,
+    mut collisions: EventReader<CollisionEvent>,
+    mut player_query: Query<(&CarryingKey, &mut TextureAtlasSprite), With<Player>>,
+    lock_query: Query<(), (With<Lock>, Without<Open>)>,
+    sfxs: Res<SfxHandles>,
+) {
+    for collision_event in collisions.iter() {
+        let CollisionEvent::Started(e1, e2, _) = collision_event else {
+            continue;
+        };
+        let (player, lock) = if lock_query.contains(*e2) {
+            (*e1, *e2)
+        } else if lock_query.contains(*e1) {
+            (*e2, *e1)
+        } else {
+            continue;
+        };
+        let Ok((carrying_key, mut sprite)) = player_query.get_mut(player) else {
+            continue;
+        };
+
+        sprite.color = Color::WHITE;
+        commands.entity(carrying_key.0).despawn_recursive();
+        commands.entity(player).remove::<CarryingKey>();
+        commands.entity(lock).insert(Open);
+        commands.play_sfx(sfxs.switch.clone());
+    }
+}
@@ -1,7 +1,13 @@
-use crate::{components::*, state::GameState};
-use bevy::{asset::LoadState, prelude::*};
+use crate::{
+    checkpoint::Checkpoints, components::*, key::CarriedBy, loading_screen::LoadingAssets,
+    room::Rooms, state::GameState,
+};
+use bevy::prelude::*;
 use bevy_ecs_ldtk::prelude::*;
 
+/// Holding R at least this long restarts the whole level instead of the last checkpoint.
+const FULL_RESTART_HOLD_SECONDS: f32 = 0.6;
+
 pub struct LevelPlugin;
 
 impl Plugin for LevelPlugin {
@@ -9,8 +15,7 @@ impl Plugin for LevelPlugin {
         app.insert_resource(LevelSelection::Index(0));
 
         // Load
-        app.add_systems(OnExit(GameState::Title), setup_ldtk_world)
-            .add_systems(Update, check_load_status.run_if(in_state(GameState::Load)));
+        app.add_systems(OnExit(GameState::Title), setup_ldtk_world);
 
         // Spawn
         app.add_systems(
@@ -27,8 +32,7 @@ impl Plugin for LevelPlugin {
             Update,
             restart_level
                 .run_if(in_state(GameState::Play).or_else(in_state(GameState::LevelClear))),
-        )
-        .add_systems(Update, camera_fit_inside_current_level);
+        );
     }
 }
 
@@ -40,76 +44,76 @@ fn move_to_level_clear_state(mut state: ResMut<NextState<GameState>>) {
     state.set(GameState::LevelClear);
 }
 
-fn setup_ldtk_world(mut commands: Commands, asset_server: Res<AssetServer>) {
+fn setup_ldtk_world(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut loading_assets: ResMut<LoadingAssets>,
+) {
+    let ldtk_handle = asset_server.load("levels.ldtk");
+    loading_assets.add(&ldtk_handle);
     commands.spawn(LdtkWorldBundle {
-        ldtk_handle: asset_server.load("levels.ldtk"),
+        ldtk_handle,
         ..Default::default()
     });
 }
 
-fn check_load_status(
-    ldtk_handle: Query<&Handle<LdtkAsset>>,
-    asset_server: Res<AssetServer>,
-    mut state: ResMut<NextState<GameState>>,
-) {
-    if let Ok(handle) = ldtk_handle.get_single() {
-        if asset_server.get_load_state(handle.clone()) == LoadState::Loaded {
-            state.set(GameState::Spawn);
-        }
-    } else {
-        println!("wait for loading...");
-    }
-}
-
-fn level_clear(q: Query<(), With<Star>>) -> bool {
-    q.is_empty()
+fn level_clear(q: Query<(), With<Star>>, rooms: Res<Rooms>) -> bool {
+    q.is_empty() && rooms.all_stars_collected()
 }
 
+#[allow(clippy::too_many_arguments)]
 fn restart_level(
     mut commands: Commands,
     level_query: Query<Entity, With<Handle<LdtkLevel>>>,
     artificial_query: Query<Entity, With<Artificial>>,
+    carried_key_query: Query<Entity, (With<CarriedBy>, Without<Artificial>)>,
     input: Res<Input<KeyCode>>,
     mut state: ResMut<NextState<GameState>>,
+    mut rooms: ResMut<Rooms>,
+    mut level_selection: ResMut<LevelSelection>,
+    mut checkpoints: ResMut<Checkpoints>,
+    mut held: Local<Option<f32>>,
+    time: Res<Time>,
 ) {
-    if input.just_pressed(KeyCode::R) {
-        for e in artificial_query.iter() {
-            commands.entity(e).despawn();
-        }
-        for level_entity in &level_query {
-            commands.entity(level_entity).insert(Respawn);
+    // without a checkpoint there is nothing to choose between, so restart right away
+    let restart_checkpoint = if checkpoints.saved.is_none() {
+        input.just_pressed(KeyCode::R)
+    } else if input.just_pressed(KeyCode::R) {
+        *held = Some(0.0);
+        false
+    } else if let Some(held_seconds) = held.as_mut() {
+        *held_seconds += time.delta_seconds();
+        if *held_seconds >= FULL_RESTART_HOLD_SECONDS {
+            *held = None;
+            checkpoints.saved = None;
+            true
+        } else if input.just_released(KeyCode::R) {
+            *held = None;
+            true
+        } else {
+            false
         }
-        state.set(GameState::Spawn);
+    } else {
+        false
+    };
+    if !restart_checkpoint {
+        return;
     }
-}
-
-#[allow(clippy::type_complexity)]
-fn camera_fit_inside_current_level(
-    mut camera_query: Query<(&mut OrthographicProjection, &mut Transform)>,
-    level_query: Query<(&Transform, &Handle<LdtkLevel>), Without<OrthographicProjection>>,
-    level_selection: Res<LevelSelection>,
-    ldtk_levels: Res<Assets<LdtkLevel>>,
-) {
-    let (mut orthographic_projection, mut camera_transform) = camera_query.single_mut();
-
-    for (level_transform, level_handle) in &level_query {
-        let Some(ldtk_level) = ldtk_levels.get(level_handle) else {
-            continue;
-        };
-        let level = &ldtk_level.level;
-        if !level_selection.is_match(&0, level) {
-            continue;
-        }
 
-        let window_size = (level.px_hei as f32)
-            .max(level.px_wid as f32)
-            .clamp(360.0, 960.0);
-        orthographic_projection.scaling_mode = bevy::render::camera::ScalingMode::Fixed {
-            width: window_size,
-            height: window_size,
-        };
-
-        camera_transform.translation.x = level_transform.translation.x + level.px_wid as f32 / 2.;
-        camera_transform.translation.y = level_transform.translation.y + level.px_hei as f32 / 2.;
+    // a carried key left over would let a player open two locks
+    for e in artificial_query.iter().chain(&carried_key_query) {
+        commands.entity(e).despawn();
+    }
+    if let Some(saved) = checkpoints.saved.as_ref() {
+        *level_selection = saved.level_selection().clone();
+        checkpoints.restoring = true;
+    } else if let Some(entry) = rooms.entry.clone() {
+        // a world of rooms restarts from the room it was entered through
+        rooms.reset_progress();
+        *level_selection = LevelSelection::Iid(entry);
+    }
+    for level_entity in &level_query {
+        commands.entity(level_entity).insert(Respawn);
     }
+    state.set(GameState::Spawn);
 }
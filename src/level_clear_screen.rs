@@ -1,4 +1,4 @@
-use crate::{constants::LEVEL_IIDS, state::GameState};
+use crate::{constants::LEVEL_IIDS, room::Rooms, state::GameState};
 use bevy::prelude::*;
 use bevy_ecs_ldtk::prelude::*;
 
@@ -60,11 +60,13 @@ fn move_to_next_level(
     mut state: ResMut<NextState<GameState>>,
     ldtk_entity: Query<Entity, With<Handle<LdtkAsset>>>,
     mut level_selection: ResMut<LevelSelection>,
+    rooms: Res<Rooms>,
 ) {
     if !input.just_pressed(KeyCode::Space) {
         return;
     }
     if let LevelSelection::Iid(ref index) = *level_selection {
+        let index = rooms.entry.as_ref().unwrap_or(index);
         let level_index = LEVEL_IIDS.iter().position(|x| x == index).unwrap();
         let e = ldtk_entity.single();
         if level_index + 1 < LEVEL_IIDS.len() {
@@ -0,0 +1,87 @@
+use crate::{loading_screen::FailedAssets, state::GameState};
+use bevy::prelude::*;
+use bevy_ecs_ldtk::prelude::*;
+
+pub struct LoadErrorScreenPlugin;
+
+impl Plugin for LoadErrorScreenPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnEnter(GameState::LoadError), spawn_load_error_screen)
+            .add_systems(
+                Update,
+                return_to_title.run_if(in_state(GameState::LoadError)),
+            )
+            .add_systems(OnExit(GameState::LoadError), despawn_load_error_screen);
+    }
+}
+
+#[derive(Component)]
+struct LoadErrorScreen;
+
+fn spawn_load_error_screen(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    failed_assets: Res<FailedAssets>,
+) {
+    let font = asset_server.load("fonts/PeaberryMono.ttf");
+    let title_style = TextStyle {
+        font: font.clone(),
+        font_size: 40.0,
+        color: Color::rgb(0.9, 0.9, 0.9),
+    };
+    let detail_style = TextStyle {
+        font,
+        font_size: 20.0,
+        color: Color::rgb(0.9, 0.9, 0.9),
+    };
+
+    commands
+        .spawn((
+            LoadErrorScreen,
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    row_gap: Val::Px(8.0),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section("Failed to load", title_style));
+            for (path, reason) in &failed_assets.0 {
+                parent.spawn(TextBundle::from_section(
+                    format!("{path}: {reason}"),
+                    detail_style.clone(),
+                ));
+            }
+            parent.spawn(TextBundle::from_section(
+                "Press Space to return to title",
+                detail_style,
+            ));
+        });
+}
+
+fn despawn_load_error_screen(mut commands: Commands, q: Query<Entity, With<LoadErrorScreen>>) {
+    for e in &q {
+        commands.entity(e).despawn_recursive();
+    }
+    commands.remove_resource::<FailedAssets>();
+}
+
+fn return_to_title(
+    mut commands: Commands,
+    input: Res<Input<KeyCode>>,
+    mut state: ResMut<NextState<GameState>>,
+    ldtk_query: Query<Entity, With<Handle<LdtkAsset>>>,
+) {
+    if input.any_just_pressed([KeyCode::Space, KeyCode::Return]) {
+        for entity in &ldtk_query {
+            commands.entity(entity).despawn_recursive();
+        }
+        state.set(GameState::Title);
+    }
+}
@@ -0,0 +1,154 @@
+use crate::state::GameState;
+use bevy::{
+    asset::{Asset, LoadState},
+    prelude::*,
+    utils::HashSet,
+};
+
+pub struct LoadingScreenPlugin;
+
+impl Plugin for LoadingScreenPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<LoadingAssets>()
+            .add_systems(OnEnter(GameState::Title), store_font_handle)
+            .add_systems(OnEnter(GameState::Load), spawn_loading_screen)
+            .add_systems(Update, check_load_status.run_if(in_state(GameState::Load)))
+            .add_systems(OnExit(GameState::Load), despawn_loading_screen);
+    }
+}
+
+/// Every asset that must be loaded before a level can be played.
+#[derive(Resource, Default)]
+pub struct LoadingAssets {
+    handles: HashSet<HandleUntyped>,
+}
+
+/// Assets that failed to load, with a short reason for each.
+#[derive(Resource, Default)]
+pub struct FailedAssets(pub Vec<(String, String)>);
+
+impl LoadingAssets {
+    pub fn add<T: Asset>(&mut self, handle: &Handle<T>) {
+        self.handles.insert(handle.clone_untyped());
+    }
+}
+
+fn store_font_handle(mut loading_assets: ResMut<LoadingAssets>, asset_server: Res<AssetServer>) {
+    let font: Handle<Font> = asset_server.load("fonts/PeaberryMono.ttf");
+    loading_assets.add(&font);
+}
+
+#[derive(Component)]
+struct LoadingScreen;
+
+#[derive(Component)]
+struct LoadingProgressBar;
+
+fn spawn_loading_screen(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands
+        .spawn((
+            LoadingScreen,
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    row_gap: Val::Px(16.0),
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "Loading",
+                TextStyle {
+                    font: asset_server.load("fonts/PeaberryMono.ttf"),
+                    font_size: 40.0,
+                    color: Color::rgb(0.9, 0.9, 0.9),
+                },
+            ));
+            parent
+                .spawn(NodeBundle {
+                    style: Style {
+                        width: Val::Percent(50.0),
+                        height: Val::Px(16.0),
+                        ..Default::default()
+                    },
+                    background_color: Color::rgba(1.0, 1.0, 1.0, 0.2).into(),
+                    ..Default::default()
+                })
+                .with_children(|bar| {
+                    bar.spawn((
+                        LoadingProgressBar,
+                        NodeBundle {
+                            style: Style {
+                                width: Val::Percent(0.0),
+                                height: Val::Percent(100.0),
+                                ..Default::default()
+                            },
+                            background_color: Color::rgb(0.9, 0.9, 0.9).into(),
+                            ..Default::default()
+                        },
+                    ));
+                });
+        });
+}
+
+fn despawn_loading_screen(mut commands: Commands, q: Query<Entity, With<LoadingScreen>>) {
+    for e in &q {
+        commands.entity(e).despawn_recursive();
+    }
+}
+
+fn check_load_status(
+    loading_assets: Res<LoadingAssets>,
+    asset_server: Res<AssetServer>,
+    mut progress_bar_query: Query<&mut Style, With<LoadingProgressBar>>,
+    mut state: ResMut<NextState<GameState>>,
+    mut commands: Commands,
+) {
+    let failed: Vec<(String, String)> = loading_assets
+        .handles
+        .iter()
+        .filter(|handle| asset_server.get_load_state(*handle) == LoadState::Failed)
+        .map(|handle| describe_failed_asset(&asset_server, handle))
+        .collect();
+    if !failed.is_empty() {
+        commands.insert_resource(FailedAssets(failed));
+        state.set(GameState::LoadError);
+        return;
+    }
+
+    let total = loading_assets.handles.len();
+    let loaded = loading_assets
+        .handles
+        .iter()
+        .filter(|handle| asset_server.get_load_state(*handle) == LoadState::Loaded)
+        .count();
+
+    if let Ok(mut style) = progress_bar_query.get_single_mut() {
+        style.width = Val::Percent(100.0 * loaded as f32 / total.max(1) as f32);
+    }
+
+    if loaded == total {
+        state.set(GameState::Spawn);
+    }
+}
+
+fn describe_failed_asset(asset_server: &AssetServer, handle: &HandleUntyped) -> (String, String) {
+    let Some(asset_path) = asset_server.get_handle_path(handle) else {
+        return ("<unknown>".to_string(), "failed to load".to_string());
+    };
+    let path = asset_path.path();
+    // the web can't query files, so the reason stays vague there
+    let reason = if cfg!(target_arch = "wasm32") {
+        "failed to load"
+    } else if asset_server.asset_io().is_file(path) {
+        "could not be read or parsed"
+    } else {
+        "file not found"
+    };
+    (path.display().to_string(), reason.to_string())
+}
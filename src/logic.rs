@@ -0,0 +1,54 @@
+use crate::{components::*, switch::Pushed};
+use bevy::{prelude::*, utils::HashMap};
+use bevy_ecs_ldtk::prelude::*;
+
+/// Propagates switch states through the logic gates, so doors can read the `Signal` of whatever drives them.
+///
+/// Scheduled by the switch plugin right before doors are updated, so a door never lags a frame behind.
+#[allow(clippy::type_complexity)]
+pub(crate) fn evaluate_signals(
+    mut switch_query: Query<(&EntityIid, &mut Signal, Option<&Pushed>), With<Switch>>,
+    mut gate_query: Query<(&EntityIid, &LogicGate, &LogicInputs, &mut Signal), Without<Switch>>,
+) {
+    let mut signals = HashMap::new();
+    for (iid, mut signal, pushed) in &mut switch_query {
+        signal.set_if_neq(Signal(pushed.is_some()));
+        signals.insert(iid.to_string(), pushed.is_some());
+    }
+    // gates start from last frame's output, which is what a latch remembers
+    for (iid, _, _, signal) in &gate_query {
+        signals.insert(iid.to_string(), signal.0);
+    }
+
+    // settle chains of gates; a loop without a latch may never settle, so give up after one pass per gate
+    for _ in 0..=gate_query.iter().len() {
+        let mut changed = false;
+        for (iid, gate, inputs, previous) in &gate_query {
+            let input = |i: usize| {
+                inputs
+                    .0
+                    .get(i)
+                    .and_then(|input_iid| signals.get(input_iid))
+                    .copied()
+                    .unwrap_or(false)
+            };
+            let on_count = (0..inputs.0.len()).filter(|&i| input(i)).count();
+            let output = match gate {
+                LogicGate::And => !inputs.0.is_empty() && on_count == inputs.0.len(),
+                LogicGate::Or => on_count > 0,
+                LogicGate::Not => !input(0),
+                LogicGate::Xor => on_count % 2 == 1,
+                LogicGate::Latch => input(0) || (previous.0 && !input(1)),
+            };
+            changed |= signals.insert(iid.to_string(), output) != Some(output);
+        }
+        if !changed {
+            break;
+        }
+    }
+
+    for (iid, _, _, mut signal) in &mut gate_query {
+        let output = signals.get(iid.as_str()).copied().unwrap_or(false);
+        signal.set_if_neq(Signal(output));
+    }
+}
@@ -9,15 +9,30 @@ use constants::{LEVEL_IIDS, WINDOW_SIZE};
 use state::GameState;
 
 mod animation;
+mod camera;
+mod checkpoint;
 mod components;
 mod constants;
+mod conveyor;
+mod entity_config;
 mod ground;
+mod key;
 mod level;
 mod level_clear_screen;
+mod load_error_screen;
+mod loading_screen;
+mod logic;
+mod music;
+mod one_way;
+mod pad;
+mod platform;
 mod player;
+mod room;
 mod sfx;
+mod size_gate;
 mod state;
 mod switch;
+mod teleporter;
 mod title_screen;
 mod ui;
 mod win_screen;
@@ -37,18 +52,36 @@ fn main() {
         )
         .add_plugins((
             LdtkPlugin,
-            RapierPhysicsPlugin::<NoUserData>::pixels_per_meter(100.0),
+            RapierPhysicsPlugin::<one_way::OneWayHooks>::pixels_per_meter(100.0),
         ))
         .add_plugins((
             animation::AnimationPlugin,
-            ground::GroundPlugin,
+            camera::CameraPlugin,
+            checkpoint::CheckpointPlugin,
+            entity_config::EntityConfigPlugin,
             level::LevelPlugin,
-            level_clear_screen::LevelClearScreenPlugin,
+            music::MusicPlugin,
             player::PlayerPlugin,
+            room::RoomPlugin,
             sfx::SfxPlugin,
+            ui::UiPlugin,
+        ))
+        .add_plugins((
+            conveyor::ConveyorPlugin,
+            ground::GroundPlugin,
+            key::KeyPlugin,
+            one_way::OneWayPlugin,
+            pad::PadPlugin,
+            platform::PlatformPlugin,
+            size_gate::SizeGatePlugin,
             switch::SwitchPlugin,
+            teleporter::TeleporterPlugin,
+        ))
+        .add_plugins((
+            level_clear_screen::LevelClearScreenPlugin,
+            load_error_screen::LoadErrorScreenPlugin,
+            loading_screen::LoadingScreenPlugin,
             title_screen::TitleScreenPlugin,
-            ui::UiPlugin,
             win_screen::WinScreenPlugin,
         ))
         .insert_resource(Msaa::Off)
@@ -57,25 +90,29 @@ fn main() {
             ..Default::default()
         })
         .insert_resource(LdtkSettings {
-            level_spawn_behavior: LevelSpawnBehavior::UseZeroTranslation,
+            level_spawn_behavior: LevelSpawnBehavior::UseWorldTranslation {
+                load_level_neighbors: false,
+            },
             set_clear_color: SetClearColor::FromLevelBackground,
             ..Default::default()
         })
         .insert_resource(LevelSelection::Iid(LEVEL_IIDS[0].to_string()))
         .add_state::<GameState>()
-        .add_systems(Startup, setup)
-        .register_ldtk_int_cell::<components::WallBundle>(1)
         .register_ldtk_entity::<components::PlayerBundle>("Player")
         .register_ldtk_entity::<components::StarBundle>("Star")
         .register_ldtk_entity::<components::SwitchBundle>("Switch")
+        .register_ldtk_entity::<components::TimedSwitchBundle>("Timed_Switch")
+        .register_ldtk_entity::<components::PressurePlateBundle>("Pressure_Plate")
+        .register_ldtk_entity::<components::LogicGateBundle>("Logic_Gate")
         .register_ldtk_entity::<components::DoorBundle>("Horizontal_Door")
         .register_ldtk_entity::<components::DoorBundle>("Vertical_Door")
+        .register_ldtk_entity::<components::LockBundle>("Lock")
+        .register_ldtk_entity::<components::SizeGateBundle>("Size_Gate")
+        .register_ldtk_entity::<components::PadBundle>("Splitter_Pad")
+        .register_ldtk_entity::<components::PadBundle>("Merger_Pad")
+        .register_ldtk_entity::<components::TeleporterBundle>("Teleporter")
+        .register_ldtk_entity::<components::MovingPlatformBundle>("Moving_Platform")
         .register_ldtk_entity::<components::UiDataBundle>("Ui_data")
         .register_ldtk_entity::<components::UiDataBundle>("Ui_long_data")
         .run();
 }
-
-pub fn setup(mut commands: Commands) {
-    let camera = Camera2dBundle::default();
-    commands.spawn(camera);
-}
@@ -0,0 +1,261 @@
+use crate::{components::Player, state::GameState};
+use bevy::{asset::LoadState, prelude::*};
+use bevy_ecs_ldtk::{ldtk::ldtk_fields::LdtkFields, prelude::*};
+use std::path::Path;
+
+const TITLE_MUSIC: &str = "music/title.wav";
+const WIN_MUSIC: &str = "music/win.wav";
+const DEFAULT_LEVEL_MUSIC: &str = "music/puzzle.wav";
+
+/// Stems layered over a track, each audible once the number of players reaches its threshold.
+///
+/// They sit in a folder named after their track, e.g. `music/puzzle/clones_2.wav`.
+const MUSIC_LAYERS: [(i32, &str); 4] = [
+    (2, "clones_2.wav"),
+    (4, "clones_4.wav"),
+    (8, "clones_8.wav"),
+    (16, "clones_16.wav"),
+];
+
+const MUSIC_VOLUME: f32 = 0.12;
+const MUSIC_FADE_SECONDS: f32 = 1.5;
+
+pub struct MusicPlugin;
+
+impl Plugin for MusicPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<MusicSelection>()
+            .add_systems(OnEnter(GameState::Title), select_title_music)
+            .add_systems(OnEnter(GameState::Win), select_win_music)
+            .add_systems(
+                Update,
+                select_level_music
+                    .before(play_selected_music)
+                    .run_if(in_state(GameState::Play)),
+            )
+            .add_systems(
+                Update,
+                (play_selected_music, stop_failed_music, crossfade_music).chain(),
+            );
+
+        app.add_systems(
+            Update,
+            (start_music_layers_together, fade_music_layers)
+                .chain()
+                .after(crossfade_music),
+        );
+    }
+}
+
+/// The track that should be heard right now, as a path under `assets/`.
+#[derive(Resource, Default, Debug, Clone, PartialEq, Eq)]
+pub struct MusicSelection(pub Option<String>);
+
+#[derive(Component)]
+struct MusicTrack {
+    path: String,
+    fading_out: bool,
+}
+
+#[derive(Component, Default)]
+struct MusicFade(f32);
+
+/// A stem spawned as a child of its `MusicTrack`, so it fades and despawns along with it.
+#[derive(Component)]
+struct MusicLayer {
+    threshold: i32,
+}
+
+/// A track is spawned paused with its layers and only starts once every stem is loaded, so they
+/// stay in time.
+#[derive(Component)]
+struct WaitingForLayers;
+
+fn music_file_exists(asset_server: &AssetServer, path: &str) -> bool {
+    // the web can't query files up front; `stop_failed_music` catches missing tracks there
+    cfg!(target_arch = "wasm32") || asset_server.asset_io().is_file(Path::new(path))
+}
+
+fn select_title_music(mut selection: ResMut<MusicSelection>) {
+    selection.set_if_neq(MusicSelection(Some(TITLE_MUSIC.to_string())));
+}
+
+fn select_win_music(mut selection: ResMut<MusicSelection>) {
+    selection.set_if_neq(MusicSelection(Some(WIN_MUSIC.to_string())));
+}
+
+/// Follows `LevelSelection`, which also moves between connected rooms without leaving `Play`.
+fn select_level_music(
+    mut selection: ResMut<MusicSelection>,
+    level_query: Query<&Handle<LdtkLevel>>,
+    level_selection: Res<LevelSelection>,
+    ldtk_levels: Res<Assets<LdtkLevel>>,
+) {
+    for level_handle in &level_query {
+        let Some(ldtk_level) = ldtk_levels.get(level_handle) else {
+            continue;
+        };
+        if !level_selection.is_match(&0, &ldtk_level.level) {
+            continue;
+        }
+        let path = ldtk_level
+            .level
+            .get_string_field("music")
+            .cloned()
+            .unwrap_or_else(|_| DEFAULT_LEVEL_MUSIC.to_string());
+        selection.set_if_neq(MusicSelection(Some(path)));
+    }
+}
+
+fn play_selected_music(
+    mut commands: Commands,
+    selection: Res<MusicSelection>,
+    mut track_query: Query<&mut MusicTrack>,
+    asset_server: Res<AssetServer>,
+) {
+    if !selection.is_changed() {
+        return;
+    }
+
+    let mut already_playing = false;
+    for mut track in &mut track_query {
+        let selected = selection.0.as_ref() == Some(&track.path);
+        track.fading_out = !selected;
+        already_playing |= selected;
+    }
+
+    let Some(path) = selection.0.as_ref() else {
+        return;
+    };
+    if already_playing {
+        return;
+    }
+    if !music_file_exists(&asset_server, path) {
+        info!("music track {path} not found, playing nothing");
+        return;
+    }
+
+    let folder = path
+        .rsplit_once('.')
+        .map_or(path.as_str(), |(folder, _)| folder);
+    commands
+        .spawn((
+            AudioBundle {
+                source: asset_server.load(path),
+                settings: PlaybackSettings::LOOP
+                    .with_volume(bevy::audio::Volume::new_relative(0.0))
+                    .paused(),
+            },
+            MusicTrack {
+                path: path.clone(),
+                fading_out: false,
+            },
+            MusicFade::default(),
+            WaitingForLayers,
+        ))
+        .with_children(|track| {
+            for (threshold, layer) in MUSIC_LAYERS {
+                let layer_path = format!("{folder}/{layer}");
+                if !music_file_exists(&asset_server, &layer_path) {
+                    continue;
+                }
+                track.spawn((
+                    AudioBundle {
+                        source: asset_server.load(layer_path),
+                        settings: PlaybackSettings::LOOP
+                            .with_volume(bevy::audio::Volume::new_relative(0.0))
+                            .paused(),
+                    },
+                    MusicLayer { threshold },
+                    MusicFade::default(),
+                ));
+            }
+        });
+}
+
+#[allow(clippy::type_complexity)]
+fn stop_failed_music(
+    mut commands: Commands,
+    track_query: Query<(Entity, &Handle<AudioSource>), Or<(With<MusicTrack>, With<MusicLayer>)>>,
+    asset_server: Res<AssetServer>,
+) {
+    for (e, source) in &track_query {
+        if asset_server.get_load_state(source) == LoadState::Failed {
+            commands.entity(e).despawn_recursive();
+        }
+    }
+}
+
+fn crossfade_music(
+    mut commands: Commands,
+    mut track_query: Query<(Entity, &MusicTrack, &mut MusicFade, Option<&AudioSink>)>,
+    time: Res<Time>,
+) {
+    let step = time.delta_seconds() / MUSIC_FADE_SECONDS;
+    for (e, track, mut fade, sink) in &mut track_query {
+        if track.fading_out {
+            fade.0 = (fade.0 - step).max(0.0);
+        } else {
+            fade.0 = (fade.0 + step).min(1.0);
+        }
+
+        if let Some(sink) = sink {
+            sink.set_volume(MUSIC_VOLUME * fade.0);
+        }
+
+        if track.fading_out && fade.0 <= 0.0 {
+            commands.entity(e).despawn_recursive();
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn start_music_layers_together(
+    mut commands: Commands,
+    waiting_query: Query<(Entity, Option<&AudioSink>, Option<&Children>), With<WaitingForLayers>>,
+    layer_query: Query<Option<&AudioSink>, With<MusicLayer>>,
+) {
+    for (e, sink, children) in &waiting_query {
+        let Some(sink) = sink else {
+            continue;
+        };
+        let layers: Option<Vec<&AudioSink>> = children
+            .into_iter()
+            .flatten()
+            .filter_map(|&child| layer_query.get(child).ok())
+            .collect();
+        let Some(layers) = layers else {
+            continue;
+        };
+
+        sink.play();
+        for layer in layers {
+            layer.play();
+        }
+        commands.entity(e).remove::<WaitingForLayers>();
+    }
+}
+
+fn fade_music_layers(
+    mut layer_query: Query<(&MusicLayer, &mut MusicFade, &Parent, Option<&AudioSink>)>,
+    track_query: Query<&MusicFade, (With<MusicTrack>, Without<MusicLayer>)>,
+    player_query: Query<(), With<Player>>,
+    time: Res<Time>,
+) {
+    // counted rather than read from `MaximumSplit`, which free pads leave untouched
+    let current = player_query.iter().count() as i32;
+    let step = time.delta_seconds() / MUSIC_FADE_SECONDS;
+    for (layer, mut fade, parent, sink) in &mut layer_query {
+        if current >= layer.threshold {
+            fade.0 = (fade.0 + step).min(1.0);
+        } else {
+            fade.0 = (fade.0 - step).max(0.0);
+        }
+
+        // a layer is never louder than its track, so it crossfades with it
+        let track_fade = track_query.get(parent.get()).map_or(0.0, |track| track.0);
+        if let Some(sink) = sink {
+            sink.set_volume(MUSIC_VOLUME * track_fade * fade.0);
+        }
+    }
+}
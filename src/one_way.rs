@@ -0,0 +1,202 @@
+use crate::{components::*, state::GameState};
+use bevy::{ecs::system::SystemParam, prelude::*};
+use bevy_ecs_ldtk::prelude::*;
+use bevy_rapier2d::{prelude::*, rapier::geometry::SolverFlags};
+use std::collections::{HashMap, HashSet};
+
+const DROP_THROUGH_SECONDS: f32 = 0.25;
+/// How far into a platform a player's feet may sink and still count as standing on it.
+const LANDING_TOLERANCE: f32 = 4.0;
+
+pub struct OneWayPlugin;
+
+impl Plugin for OneWayPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(Update, spawn_one_way_collision)
+            .add_systems(
+                Update,
+                (drop_through, stop_dropping).run_if(in_state(GameState::Play)),
+            );
+    }
+}
+
+/// A merged strip of one-way cells that players can jump up through and stand on.
+#[derive(Component)]
+pub struct OneWayPlatform;
+
+/// A player falling through one-way platforms after pressing down.
+#[derive(Component)]
+struct DroppingThrough(Timer);
+
+/// Lets players through one-way platforms unless they are above them and not dropping.
+#[derive(SystemParam)]
+pub struct OneWayHooks<'w, 's> {
+    platform_query:
+        Query<'w, 's, (&'static GlobalTransform, &'static Collider), With<OneWayPlatform>>,
+    player_query: Query<
+        'w,
+        's,
+        (
+            &'static GlobalTransform,
+            &'static Collider,
+            Option<&'static DroppingThrough>,
+        ),
+        With<Player>,
+    >,
+    ground_sensor_query: Query<'w, 's, &'static GroundSensor>,
+}
+
+impl OneWayHooks<'_, '_> {
+    fn platform_and_other(&self, context: &PairFilterContextView) -> Option<(Entity, Entity)> {
+        let (e1, e2) = (context.collider1(), context.collider2());
+        if self.platform_query.contains(e1) {
+            Some((e1, e2))
+        } else if self.platform_query.contains(e2) {
+            Some((e2, e1))
+        } else {
+            None
+        }
+    }
+
+    fn holds_up(&self, platform: Entity, player: Entity) -> bool {
+        let (
+            Ok((platform_transform, platform_collider)),
+            Ok((player_transform, player_collider, dropping)),
+        ) = (
+            self.platform_query.get(platform),
+            self.player_query.get(player),
+        )
+        else {
+            return false;
+        };
+        if dropping.is_some() {
+            return false;
+        }
+        let half_height =
+            |collider: &Collider| collider.as_cuboid().map_or(0., |c| c.half_extents().y);
+        let platform_top = platform_transform.translation().y + half_height(platform_collider);
+        let player_bottom = player_transform.translation().y - half_height(player_collider);
+        player_bottom >= platform_top - LANDING_TOLERANCE
+    }
+}
+
+impl BevyPhysicsHooks for OneWayHooks<'_, '_> {
+    fn filter_contact_pair(&self, context: PairFilterContextView) -> Option<SolverFlags> {
+        let (platform, other) = self.platform_and_other(&context)?;
+        self.holds_up(platform, other)
+            .then_some(SolverFlags::COMPUTE_IMPULSES)
+    }
+
+    /// Ground sensors only see platforms that would hold their player up.
+    fn filter_intersection_pair(&self, context: PairFilterContextView) -> bool {
+        let Some((platform, other)) = self.platform_and_other(&context) else {
+            return false;
+        };
+        self.ground_sensor_query
+            .get(other)
+            .is_ok_and(|sensor| self.holds_up(platform, sensor.ground_detection_entity))
+    }
+}
+
+/// One-way cells are merged into horizontal strips, one per run of cells in a row.
+fn spawn_one_way_collision(
+    mut commands: Commands,
+    cell_query: Query<(&GridCoords, &Parent), Added<OneWay>>,
+    parent_query: Query<&Parent, Without<OneWay>>,
+    level_query: Query<(Entity, &Handle<LdtkLevel>)>,
+    levels: Res<Assets<LdtkLevel>>,
+) {
+    if cell_query.is_empty() {
+        return;
+    }
+
+    let mut level_to_cells: HashMap<Entity, HashSet<GridCoords>> = HashMap::new();
+    for (&grid_coords, parent) in &cell_query {
+        if let Ok(grandparent) = parent_query.get(parent.get()) {
+            level_to_cells
+                .entry(grandparent.get())
+                .or_default()
+                .insert(grid_coords);
+        }
+    }
+
+    for (level_entity, level_handle) in &level_query {
+        let Some(cells) = level_to_cells.get(&level_entity) else {
+            continue;
+        };
+        let Some(level) = levels.get(level_handle) else {
+            continue;
+        };
+        let Some(LayerInstance {
+            c_wid: width,
+            c_hei: height,
+            grid_size,
+            ..
+        }) = level.level.layer_instances.as_ref().and_then(|l| l.first())
+        else {
+            continue;
+        };
+        let grid_size = *grid_size as f32;
+
+        commands.entity(level_entity).with_children(|level| {
+            for y in 0..*height {
+                let mut strip_start = None;
+                for x in 0..width + 1 {
+                    match (strip_start, cells.contains(&GridCoords { x, y })) {
+                        (None, true) => strip_start = Some(x),
+                        (Some(left), false) => {
+                            let length = (x - left) as f32 * grid_size;
+                            level.spawn((
+                                Collider::cuboid(length / 2., grid_size / 2.),
+                                RigidBody::Fixed,
+                                Friction::new(1.0),
+                                ActiveHooks::FILTER_CONTACT_PAIRS
+                                    | ActiveHooks::FILTER_INTERSECTION_PAIR,
+                                OneWayPlatform,
+                                TransformBundle::from_transform(Transform::from_xyz(
+                                    left as f32 * grid_size + length / 2.,
+                                    (y as f32 + 0.5) * grid_size,
+                                    0.,
+                                )),
+                            ));
+                            strip_start = None;
+                        }
+                        _ => (),
+                    }
+                }
+            }
+        });
+    }
+}
+
+fn drop_through(
+    mut commands: Commands,
+    input: Res<Input<KeyCode>>,
+    player_query: Query<(Entity, &GroundDetection), With<Player>>,
+) {
+    if !input.any_just_pressed([KeyCode::S, KeyCode::Down]) {
+        return;
+    }
+    for (e, ground_detection) in &player_query {
+        if ground_detection.on_ground {
+            commands
+                .entity(e)
+                .insert(DroppingThrough(Timer::from_seconds(
+                    DROP_THROUGH_SECONDS,
+                    TimerMode::Once,
+                )));
+        }
+    }
+}
+
+fn stop_dropping(
+    mut commands: Commands,
+    mut dropping_query: Query<(Entity, &mut DroppingThrough)>,
+    time: Res<Time>,
+) {
+    for (e, mut dropping) in &mut dropping_query {
+        if dropping.0.tick(time.delta()).just_finished() {
+            commands.entity(e).remove::<DroppingThrough>();
+        }
+    }
+}
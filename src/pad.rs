@@ -0,0 +1,144 @@
+use crate::{
+    components::*,
+    player::{player_clone_bundle, split_player, Locked, MaximumSplit, PlayerTexture},
+    sfx::{AudioControler, SfxHandles},
+    state::GameState,
+};
+use bevy::{prelude::*, utils::HashSet};
+use bevy_ecs_ldtk::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+pub struct PadPlugin;
+
+impl Plugin for PadPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (split_on_pads, merge_on_pads).run_if(in_state(GameState::Play)),
+        );
+    }
+}
+
+/// Splitter pads split whoever steps on them; clones thrown out of a split are still locked, so
+/// they don't split again on landing.
+#[allow(clippy::too_many_arguments)]
+fn split_on_pads(
+    mut commands: Commands,
+    mut collisions: EventReader<CollisionEvent>,
+    pad_query: Query<&Pad>,
+    player_query: Query<(&GlobalTransform, &Player), Without<Locked>>,
+    ldtk_query: Query<Entity, With<Handle<LdtkAsset>>>,
+    player_texture: Res<PlayerTexture>,
+    mut maximum_split: Option<ResMut<MaximumSplit>>,
+    sfxs: Res<SfxHandles>,
+) {
+    let mut split = HashSet::new();
+    for collision_event in collisions.iter() {
+        let CollisionEvent::Started(e1, e2, _) = collision_event else {
+            continue;
+        };
+        let (pad, player) = match (pad_query.get(*e1), pad_query.get(*e2)) {
+            (Ok(pad), _) => (pad, *e2),
+            (_, Ok(pad)) => (pad, *e1),
+            _ => continue,
+        };
+        if pad.merger || split.contains(&player) {
+            continue;
+        }
+        let Ok((transform, player_component)) = player_query.get(player) else {
+            continue;
+        };
+        let Ok(ldtk_entity) = ldtk_query.get_single() else {
+            continue;
+        };
+        // free pads are a bonus on top of the level's split budget, so they leave it untouched
+        if pad.consumes_split {
+            let Some(maximum_split) = maximum_split.as_mut() else {
+                continue;
+            };
+            if maximum_split.current + 1 > maximum_split.max {
+                continue;
+            }
+            maximum_split.current += 1;
+        }
+
+        split_player(
+            &mut commands,
+            &player_texture,
+            ldtk_entity,
+            player,
+            transform,
+            player_component,
+        );
+        split.insert(player);
+    }
+
+    if !split.is_empty() {
+        commands.play_sfx(sfxs.split.clone());
+    }
+}
+
+/// Merger pads fuse two players of the same size standing on them into one of the size before.
+#[allow(clippy::too_many_arguments)]
+fn merge_on_pads(
+    mut commands: Commands,
+    pad_query: Query<(Entity, &Pad)>,
+    player_query: Query<(&GlobalTransform, &Player), Without<Locked>>,
+    rapier_context: Res<RapierContext>,
+    ldtk_query: Query<Entity, With<Handle<LdtkAsset>>>,
+    player_texture: Res<PlayerTexture>,
+    mut maximum_split: Option<ResMut<MaximumSplit>>,
+    sfxs: Res<SfxHandles>,
+) {
+    let Ok(ldtk_entity) = ldtk_query.get_single() else {
+        return;
+    };
+
+    let mut merged = HashSet::new();
+    for (pad_entity, pad) in &pad_query {
+        if !pad.merger {
+            continue;
+        }
+        let standing: Vec<_> = rapier_context
+            .intersections_with(pad_entity)
+            .filter(|(_, _, intersecting)| *intersecting)
+            .map(|(e1, e2, _)| if e1 == pad_entity { e2 } else { e1 })
+            .filter(|e| !merged.contains(e))
+            .filter_map(|e| player_query.get(e).ok().map(|(t, p)| (e, t, p)))
+            .collect();
+
+        let pair = standing.iter().enumerate().find_map(|(i, a)| {
+            standing[i + 1..]
+                .iter()
+                .find(|b| a.2.level > 0 && a.2.level == b.2.level)
+                .map(|b| (a, b))
+        });
+        let Some(((e1, transform1, player), (e2, transform2, _))) = pair else {
+            continue;
+        };
+
+        let center = (transform1.translation() + transform2.translation()) / 2.;
+        let merged_player = commands
+            .spawn((
+                player_clone_bundle(
+                    &player_texture,
+                    player.level - 1,
+                    Transform::from_translation(center),
+                    Vec2::ZERO,
+                ),
+                Artificial,
+            ))
+            .id();
+        commands.entity(ldtk_entity).add_child(merged_player);
+        commands.entity(*e1).despawn_recursive();
+        commands.entity(*e2).despawn_recursive();
+        merged.extend([*e1, *e2]);
+
+        if pad.consumes_split {
+            if let Some(maximum_split) = maximum_split.as_mut() {
+                maximum_split.current -= 1;
+            }
+        }
+        commands.play_sfx(sfxs.split.clone());
+    }
+}
@@ -0,0 +1,101 @@
+use crate::{
+    components::*,
+    state::GameState,
+    switch::{slide_doors, DoorTrack},
+};
+use bevy::prelude::*;
+use bevy_ecs_ldtk::prelude::*;
+
+pub struct PlatformPlugin;
+
+impl Plugin for PlatformPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (move_platforms, carry_riders.after(slide_doors))
+                .chain()
+                .run_if(in_state(GameState::Play)),
+        );
+    }
+}
+
+fn move_platforms(
+    mut platform_query: Query<(&EntityIid, &mut Transform, &mut MovingPlatform)>,
+    source_query: Query<(&RelatedDoors, &Signal)>,
+    time: Res<Time>,
+) {
+    for (iid, mut transform, mut platform) in &mut platform_query {
+        platform.step = Vec3::ZERO;
+
+        let origin = *platform.origin.get_or_insert(transform.translation);
+        let mut wired = source_query
+            .iter()
+            .filter(|(related, _)| related.0.iter().any(|target| target == iid.as_str()))
+            .peekable();
+        let active = wired.peek().is_none() || wired.any(|(_, signal)| signal.0);
+        if !active || platform.path.len() < 2 {
+            continue;
+        }
+
+        let mut distance = platform.speed * time.delta_seconds();
+        let start = transform.translation;
+        // a path of repeated points would never use the distance up
+        for _ in 0..platform.path.len() {
+            let target = origin + platform.path[platform.target].extend(0.);
+            let remaining = target - transform.translation;
+            if remaining.length() > distance {
+                transform.translation += remaining.normalize() * distance;
+                break;
+            }
+            transform.translation = target;
+            distance -= remaining.length();
+            advance_target(&mut platform);
+        }
+        platform.step = transform.translation - start;
+    }
+}
+
+fn advance_target(platform: &mut MovingPlatform) {
+    let last = platform.path.len() - 1;
+    match platform.mode {
+        PathMode::Loop => platform.target = (platform.target + 1) % platform.path.len(),
+        PathMode::PingPong => {
+            if platform.target == last {
+                platform.forward = false;
+            } else if platform.target == 0 {
+                platform.forward = true;
+            }
+            if platform.forward {
+                platform.target += 1;
+            } else {
+                platform.target -= 1;
+            }
+        }
+    }
+}
+
+/// Players whose ground sensor touches a platform or a sliding door move along with it.
+fn carry_riders(
+    sensor_query: Query<&GroundSensor>,
+    platform_query: Query<&MovingPlatform>,
+    door_query: Query<&DoorTrack>,
+    mut player_query: Query<&mut Transform, With<Player>>,
+) {
+    for sensor in &sensor_query {
+        let Some(step) = platform_query
+            .iter_many(&sensor.intersecting_ground_entities)
+            .map(|platform| platform.step)
+            .chain(
+                door_query
+                    .iter_many(&sensor.intersecting_ground_entities)
+                    .map(|track| track.step),
+            )
+            .find(|step| *step != Vec3::ZERO)
+        else {
+            continue;
+        };
+        if let Ok(mut transform) = player_query.get_mut(sensor.ground_detection_entity) {
+            transform.translation += step;
+        }
+    }
+}
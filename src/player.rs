@@ -1,21 +1,29 @@
 use crate::{
     animation::{Animation, AnimationSetting, Animations},
     components::*,
+    conveyor::Conveyors,
+    loading_screen::LoadingAssets,
     sfx::{AudioControler, SfxHandles},
     state::GameState,
 };
 use bevy::prelude::*;
-use bevy_ecs_ldtk::{ldtk::ldtk_fields::LdtkFields, LdtkAsset, LdtkLevel};
+use bevy_ecs_ldtk::{
+    ldtk::ldtk_fields::LdtkFields, EntityIid, LdtkAsset, LdtkLevel, LevelSelection,
+};
 use bevy_rapier2d::prelude::*;
 
 const PLAYER_VELOCITY: f32 = 120.0;
 const PLAYER_JUMP_VELOCITY: f32 = 300.0;
+const PLAYER_ACCELERATION: f32 = 2400.0;
+const ICE_ACCELERATION: f32 = 180.0;
+const STICKY_VELOCITY_FACTOR: f32 = 0.4;
 
 pub struct PlayerPlugin;
 
 impl Plugin for PlayerPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(OnEnter(GameState::Title), store_player_texture_handle);
+        app.add_event::<StarCollected>()
+            .add_systems(OnEnter(GameState::Title), store_player_texture_handle);
 
         app.add_systems(
             Update,
@@ -45,11 +53,15 @@ impl Plugin for PlayerPlugin {
 #[derive(Copy, Clone, PartialEq, Debug, Default, Component)]
 pub struct SpawnTimer(f32);
 
+/// Sent with the iid of a star when a player collects it.
+#[derive(Event)]
+pub struct StarCollected(pub String);
+
 #[derive(Copy, Clone, PartialEq, Debug, Default, Component)]
 pub struct Locked;
 
 #[derive(Resource)]
-struct PlayerTexture {
+pub(crate) struct PlayerTexture {
     handle: Handle<TextureAtlas>,
 }
 
@@ -57,8 +69,10 @@ fn store_player_texture_handle(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
     mut texture_atlases: ResMut<Assets<TextureAtlas>>,
+    mut loading_assets: ResMut<LoadingAssets>,
 ) {
     let texture_handle = asset_server.load("atlas/player.png");
+    loading_assets.add(&texture_handle);
     let texture_atlas =
         TextureAtlas::from_grid(texture_handle, (32.0, 32.0).into(), 6, 5, None, None);
     let texture_atlas_handle = texture_atlases.add(texture_atlas);
@@ -73,6 +87,8 @@ fn movement(
     input: Res<Input<KeyCode>>,
     mut query: Query<(&mut Velocity, &GroundDetection), (With<Player>, Without<Locked>)>,
     sfxs: Res<SfxHandles>,
+    time: Res<Time>,
+    conveyors: Res<Conveyors>,
 ) {
     let mut any_jumped = false;
     for (mut velocity, ground_detection) in &mut query {
@@ -87,9 +103,30 @@ fn movement(
             0.
         };
 
-        velocity.linvel.x = (right - left) * PLAYER_VELOCITY;
-
-        if input.just_pressed(KeyCode::Space) && ground_detection.on_ground {
+        let material = if ground_detection.on_ground {
+            ground_detection.material
+        } else {
+            TerrainMaterial::Normal
+        };
+        let (max_velocity, acceleration) = match material {
+            TerrainMaterial::Ice => (PLAYER_VELOCITY, ICE_ACCELERATION),
+            TerrainMaterial::Sticky => (
+                PLAYER_VELOCITY * STICKY_VELOCITY_FACTOR,
+                PLAYER_ACCELERATION,
+            ),
+            TerrainMaterial::Normal
+            | TerrainMaterial::Bouncy
+            | TerrainMaterial::ConveyorLeft
+            | TerrainMaterial::ConveyorRight => (PLAYER_VELOCITY, PLAYER_ACCELERATION),
+        };
+        let target = (right - left) * max_velocity + conveyors.velocity(material);
+        let max_change = acceleration * time.delta_seconds();
+        velocity.linvel.x += (target - velocity.linvel.x).clamp(-max_change, max_change);
+
+        if input.just_pressed(KeyCode::Space)
+            && ground_detection.on_ground
+            && material != TerrainMaterial::Sticky
+        {
             velocity.linvel.y = PLAYER_JUMP_VELOCITY;
             any_jumped = true;
         }
@@ -111,7 +148,7 @@ fn split_into_two(
     input: Res<Input<KeyCode>>,
     mut commands: Commands,
     player_texture: Res<PlayerTexture>,
-    player_query: Query<(Entity, &Transform, &Player)>,
+    player_query: Query<(Entity, &GlobalTransform, &Player)>,
     ldtk_query: Query<Entity, With<Handle<LdtkAsset>>>,
     mut maximum_split: Option<ResMut<MaximumSplit>>,
     sfxs: Res<SfxHandles>,
@@ -123,6 +160,9 @@ fn split_into_two(
     let Some(maximum_split) = maximum_split.as_mut() else {
         return;
     };
+    let Ok(ldtk_entity) = ldtk_query.get_single() else {
+        return;
+    };
 
     if maximum_split.current * 2 > maximum_split.max {
         return;
@@ -130,87 +170,100 @@ fn split_into_two(
     maximum_split.current *= 2;
 
     for (e, transform, player) in player_query.iter() {
-        let next_player_level = player.level + 1;
-        let collider_size = convert_player_level_to_collider_size(next_player_level);
-        let players = [
-            commands
-                .spawn((
-                    PlayerBundle {
-                        sprite_bundle: SpriteSheetBundle {
-                            texture_atlas: player_texture.handle.clone(),
-                            sprite: TextureAtlasSprite::new(next_player_level.min(5) as usize),
-                            transform: (*transform).with_translation(
-                                transform.translation + Vec3::new(collider_size * 1.05, 0.0, 0.0),
-                            ),
-                            ..Default::default()
-                        },
-                        collider_bundle: ColliderBundle {
-                            collider: Collider::cuboid(collider_size, collider_size),
-                            rigid_body: RigidBody::Dynamic,
-                            velocity: Velocity::linear(
-                                (PLAYER_VELOCITY, PLAYER_JUMP_VELOCITY * 2. / 3.).into(),
-                            ),
-                            rotation_constraints: LockedAxes::ROTATION_LOCKED,
-                            friction: Friction {
-                                coefficient: 0.0,
-                                combine_rule: CoefficientCombineRule::Min,
-                            },
-                            ..Default::default()
-                        },
-                        player: Player {
-                            level: next_player_level,
-                        },
-                        ..Default::default()
-                    },
-                    SpawnTimer(0.2),
-                    Locked,
-                    Artificial,
-                ))
-                .id(),
-            commands
-                .spawn((
-                    PlayerBundle {
-                        sprite_bundle: SpriteSheetBundle {
-                            texture_atlas: player_texture.handle.clone(),
-                            sprite: TextureAtlasSprite::new(next_player_level.min(5) as usize),
-                            transform: (*transform).with_translation(
-                                transform.translation + Vec3::new(-collider_size * 1.05, 0.0, 0.0),
-                            ),
-                            ..Default::default()
-                        },
-                        collider_bundle: ColliderBundle {
-                            collider: Collider::cuboid(collider_size, collider_size),
-                            rigid_body: RigidBody::Dynamic,
-                            velocity: Velocity::linear(
-                                (-PLAYER_VELOCITY, PLAYER_JUMP_VELOCITY * 2. / 3.).into(),
-                            ),
-                            rotation_constraints: LockedAxes::ROTATION_LOCKED,
-                            friction: Friction {
-                                coefficient: 0.0,
-                                combine_rule: CoefficientCombineRule::Min,
-                            },
-                            ..Default::default()
-                        },
-                        player: Player {
-                            level: next_player_level,
-                        },
-                        ..Default::default()
-                    },
-                    SpawnTimer(0.1),
-                    Locked,
-                    Artificial,
-                    ActiveEvents::empty(),
-                ))
-                .id(),
-        ];
-        commands.entity(e).despawn();
-        let ldtk_entity = ldtk_query.single();
-        commands.entity(ldtk_entity).push_children(&players);
+        split_player(
+            &mut commands,
+            &player_texture,
+            ldtk_entity,
+            e,
+            transform,
+            player,
+        );
     }
 
     commands.play_sfx(sfxs.split.clone());
 }
 
+/// Replaces a player with two clones of the next level, thrown apart to either side.
+pub(crate) fn split_player(
+    commands: &mut Commands,
+    player_texture: &PlayerTexture,
+    ldtk_entity: Entity,
+    e: Entity,
+    transform: &GlobalTransform,
+    player: &Player,
+) {
+    // clones live on the world entity, so they are placed in world space
+    let transform = transform.compute_transform();
+    let next_player_level = player.level + 1;
+    let collider_size = convert_player_level_to_collider_size(next_player_level);
+    let players = [
+        commands
+            .spawn((
+                player_clone_bundle(
+                    player_texture,
+                    next_player_level,
+                    transform.with_translation(
+                        transform.translation + Vec3::new(collider_size * 1.05, 0.0, 0.0),
+                    ),
+                    Vec2::new(PLAYER_VELOCITY, PLAYER_JUMP_VELOCITY * 2. / 3.),
+                ),
+                SpawnTimer(0.2),
+                Locked,
+                Artificial,
+            ))
+            .id(),
+        commands
+            .spawn((
+                player_clone_bundle(
+                    player_texture,
+                    next_player_level,
+                    transform.with_translation(
+                        transform.translation + Vec3::new(-collider_size * 1.05, 0.0, 0.0),
+                    ),
+                    Vec2::new(-PLAYER_VELOCITY, PLAYER_JUMP_VELOCITY * 2. / 3.),
+                ),
+                SpawnTimer(0.1),
+                Locked,
+                Artificial,
+                ActiveEvents::empty(),
+            ))
+            .id(),
+    ];
+    commands.entity(e).despawn_recursive();
+    commands.entity(ldtk_entity).push_children(&players);
+}
+
+/// A player body of the given level, as spawned at runtime rather than from LDtk.
+pub(crate) fn player_clone_bundle(
+    player_texture: &PlayerTexture,
+    level: u8,
+    transform: Transform,
+    linvel: Vec2,
+) -> PlayerBundle {
+    let collider_size = convert_player_level_to_collider_size(level);
+    PlayerBundle {
+        sprite_bundle: SpriteSheetBundle {
+            texture_atlas: player_texture.handle.clone(),
+            sprite: TextureAtlasSprite::new(level.min(5) as usize),
+            transform,
+            ..Default::default()
+        },
+        collider_bundle: ColliderBundle {
+            collider: Collider::cuboid(collider_size, collider_size),
+            rigid_body: RigidBody::Dynamic,
+            velocity: Velocity::linear(linvel),
+            rotation_constraints: LockedAxes::ROTATION_LOCKED,
+            friction: Friction {
+                coefficient: 0.0,
+                combine_rule: CoefficientCombineRule::Min,
+            },
+            ..Default::default()
+        },
+        player: Player { level },
+        ..Default::default()
+    }
+}
+
 fn count_spawn_timer(
     mut commands: Commands,
     mut query: Query<(Entity, &mut SpawnTimer), With<Player>>,
@@ -270,8 +323,9 @@ fn star_set_twinkle_animation(
 fn star_despawn(
     mut commands: Commands,
     player_query: Query<Entity, With<Player>>,
-    star_query: Query<(Entity, &Transform), (With<Star>, Without<Player>)>,
+    star_query: Query<(Entity, &GlobalTransform, &EntityIid), (With<Star>, Without<Player>)>,
     mut collisions: EventReader<CollisionEvent>,
+    mut collected_stars: EventWriter<StarCollected>,
     sfxs: Res<SfxHandles>,
     animation: Res<Animations>,
 ) {
@@ -286,7 +340,8 @@ fn star_despawn(
 
                 commands.play_sfx(sfxs.star.clone());
 
-                let transform: &Transform = star_query.get_component(*e2).unwrap();
+                let (_, transform, iid) = star_query.get(*e2).unwrap();
+                collected_stars.send(StarCollected(iid.to_string()));
                 let (texture_atlas, sprite, animation_setting) = animation
                     .animations
                     .get(&Animation::DespawnStar)
@@ -296,7 +351,7 @@ fn star_despawn(
                     SpriteSheetBundle {
                         texture_atlas,
                         sprite,
-                        transform: *transform,
+                        transform: transform.compute_transform(),
                         ..Default::default()
                     },
                     animation_setting,
@@ -311,7 +366,8 @@ fn star_despawn(
 
                 commands.play_sfx(sfxs.star.clone());
 
-                let transform: &Transform = star_query.get_component(*e1).unwrap();
+                let (_, transform, iid) = star_query.get(*e1).unwrap();
+                collected_stars.send(StarCollected(iid.to_string()));
                 let (texture_atlas, sprite, animation_setting) = animation
                     .animations
                     .get(&Animation::DespawnStar)
@@ -321,7 +377,7 @@ fn star_despawn(
                     SpriteSheetBundle {
                         texture_atlas,
                         sprite,
-                        transform: *transform,
+                        transform: transform.compute_transform(),
                         ..Default::default()
                     },
                     animation_setting,
@@ -345,24 +401,30 @@ fn convert_player_level_to_collider_size(level: u8) -> f32 {
 
 #[derive(Clone, Copy, Default, Debug, Resource)]
 pub struct MaximumSplit {
-    max: i32,
-    current: i32,
+    pub max: i32,
+    /// Bodies spent out of `max`; pads that don't consume splits deliberately bypass it, so it
+    /// isn't always the number of players.
+    pub current: i32,
 }
 
 #[derive(Component)]
 struct MaximumSplitUi;
 
-fn store_maximum_split_in_level(
+pub(crate) fn store_maximum_split_in_level(
     mut commands: Commands,
     player_query: Query<(), With<Player>>,
     level_query: Query<(Entity, &Handle<LdtkLevel>)>,
     level_asset: Res<Assets<LdtkLevel>>,
+    level_selection: Res<LevelSelection>,
     asset_server: Res<AssetServer>,
 ) {
-    let Ok((e, handle)) = level_query.get_single() else {
-        return;
-    };
-    let Some(level): Option<&LdtkLevel> = level_asset.get(handle) else {
+    // connected rooms keep several levels spawned, only the selected one holds the split count
+    let Some((e, level)) = level_query.iter().find_map(|(e, handle)| {
+        level_asset
+            .get(handle)
+            .filter(|level| level_selection.is_match(&0, &level.level))
+            .map(|level| (e, level))
+    }) else {
         return;
     };
     let max_split = level.level.get_int_field("max_split").cloned().unwrap_or(8);
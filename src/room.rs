@@ -0,0 +1,268 @@
+use crate::{
+    components::*,
+    player::StarCollected,
+    state::GameState,
+    switch::{Open, Pushed},
+};
+use bevy::{prelude::*, utils::HashSet};
+use bevy_ecs_ldtk::{ldtk::ldtk_fields::LdtkFields, prelude::*};
+
+pub struct RoomPlugin;
+
+impl Plugin for RoomPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Rooms>()
+            .add_systems(
+                Update,
+                (
+                    enter_or_leave_room_world,
+                    adopt_room_players,
+                    restore_room_progress,
+                )
+                    .chain(),
+            )
+            .add_systems(
+                Update,
+                (record_room_progress, follow_players_into_rooms).run_if(in_state(GameState::Play)),
+            );
+    }
+}
+
+/// Progress through a world of connected rooms (levels with the `connected_room` field set).
+///
+/// Entity iids are unique across the project, so collected stars, opened doors and pushed
+/// switches are remembered per room without keying them by level.
+#[derive(Resource, Default, Clone)]
+pub struct Rooms {
+    pub entry: Option<String>,
+    rooms: HashSet<String>,
+    stars: HashSet<String>,
+    collected_stars: HashSet<String>,
+    opened_doors: HashSet<String>,
+    pushed_switches: HashSet<String>,
+    spawned_players: HashSet<String>,
+}
+
+impl Rooms {
+    pub fn is_active(&self) -> bool {
+        self.entry.is_some()
+    }
+
+    /// Stars in rooms that aren't loaded can't be seen by a query, so they are counted here.
+    pub fn all_stars_collected(&self) -> bool {
+        self.stars.is_subset(&self.collected_stars)
+    }
+
+    pub fn reset_progress(&mut self) {
+        self.collected_stars.clear();
+        self.opened_doors.clear();
+        self.pushed_switches.clear();
+        self.spawned_players.clear();
+    }
+}
+
+fn is_connected_room(level: &bevy_ecs_ldtk::ldtk::Level) -> bool {
+    level
+        .get_bool_field("connected_room")
+        .copied()
+        .unwrap_or(false)
+}
+
+fn enter_or_leave_room_world(
+    mut rooms: ResMut<Rooms>,
+    mut ldtk_settings: ResMut<LdtkSettings>,
+    level_selection: Res<LevelSelection>,
+    ldtk_query: Query<&Handle<LdtkAsset>>,
+    ldtk_assets: Res<Assets<LdtkAsset>>,
+) {
+    let Ok(ldtk_handle) = ldtk_query.get_single() else {
+        return;
+    };
+    let Some(ldtk_asset) = ldtk_assets.get(ldtk_handle) else {
+        return;
+    };
+    let Some(selected) = ldtk_asset.get_level(&level_selection) else {
+        return;
+    };
+
+    if !is_connected_room(selected) {
+        if rooms.is_active() {
+            *rooms = Rooms::default();
+            ldtk_settings.level_spawn_behavior = LevelSpawnBehavior::UseWorldTranslation {
+                load_level_neighbors: false,
+            };
+        }
+        return;
+    }
+    if rooms.rooms.contains(&selected.iid) {
+        return;
+    }
+
+    // walk the neighbours to find every room of this world
+    let mut world_rooms = HashSet::new();
+    let mut queue = vec![selected.iid.clone()];
+    while let Some(iid) = queue.pop() {
+        let Some(level) = ldtk_asset.get_level(&LevelSelection::Iid(iid.clone())) else {
+            continue;
+        };
+        if !is_connected_room(level) || !world_rooms.insert(iid) {
+            continue;
+        }
+        queue.extend(level.neighbours.iter().map(|n| n.level_iid.clone()));
+    }
+
+    let stars = world_rooms
+        .iter()
+        .filter_map(|iid| ldtk_asset.get_level(&LevelSelection::Iid(iid.clone())))
+        .flat_map(|level| level.layer_instances.iter().flatten())
+        .flat_map(|layer| &layer.entity_instances)
+        .filter(|entity_instance| entity_instance.identifier == "Star")
+        .map(|entity_instance| entity_instance.iid.clone())
+        .collect();
+
+    *rooms = Rooms {
+        entry: Some(selected.iid.clone()),
+        rooms: world_rooms,
+        stars,
+        ..Default::default()
+    };
+    ldtk_settings.level_spawn_behavior = LevelSpawnBehavior::UseWorldTranslation {
+        load_level_neighbors: true,
+    };
+}
+
+/// Players are moved onto the world so they survive their room being unloaded.
+fn adopt_room_players(
+    mut commands: Commands,
+    mut rooms: ResMut<Rooms>,
+    player_query: Query<(Entity, &EntityIid), Added<Player>>,
+) {
+    if !rooms.is_active() {
+        return;
+    }
+    for (e, iid) in &player_query {
+        if rooms.spawned_players.insert(iid.to_string()) {
+            commands.entity(e).insert((
+                Worldly {
+                    entity_iid: iid.to_string(),
+                },
+                Artificial,
+            ));
+        } else {
+            commands.entity(e).despawn_recursive();
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn restore_room_progress(
+    mut commands: Commands,
+    rooms: Res<Rooms>,
+    star_query: Query<(Entity, &EntityIid), (With<Star>, Added<EntityIid>)>,
+    door_query: Query<(Entity, &Door), Added<Door>>,
+    switch_query: Query<(Entity, &EntityIid), (With<Switch>, Added<EntityIid>)>,
+) {
+    if !rooms.is_active() {
+        return;
+    }
+    for (e, iid) in &star_query {
+        if rooms.collected_stars.contains(iid.as_str()) {
+            commands.entity(e).despawn_recursive();
+        }
+    }
+    for (e, door) in &door_query {
+        if rooms.opened_doors.contains(&door.0) {
+            commands.entity(e).insert(Open);
+        }
+    }
+    for (e, iid) in &switch_query {
+        if rooms.pushed_switches.contains(iid.as_str()) {
+            commands.entity(e).insert(Pushed);
+        }
+    }
+}
+
+#[allow(clippy::type_complexity)]
+fn record_room_progress(
+    mut rooms: ResMut<Rooms>,
+    mut collected_stars: EventReader<StarCollected>,
+    opened_door_query: Query<&Door, Added<Open>>,
+    door_query: Query<&Door>,
+    mut closed_doors: RemovedComponents<Open>,
+    pushed_switch_query: Query<
+        &EntityIid,
+        (
+            With<Switch>,
+            Added<Pushed>,
+            Without<PressurePlate>,
+            Without<SwitchDuration>,
+        ),
+    >,
+) {
+    if !rooms.is_active() {
+        collected_stars.clear();
+        closed_doors.clear();
+        return;
+    }
+    for StarCollected(iid) in collected_stars.iter() {
+        rooms.collected_stars.insert(iid.clone());
+    }
+    for door in door_query.iter_many(closed_doors.iter()) {
+        rooms.opened_doors.remove(&door.0);
+    }
+    for door in &opened_door_query {
+        rooms.opened_doors.insert(door.0.clone());
+    }
+    for iid in &pushed_switch_query {
+        rooms.pushed_switches.insert(iid.to_string());
+    }
+}
+
+/// Selecting the room a player walked into also loads its neighbours and moves the camera there.
+fn follow_players_into_rooms(
+    rooms: Res<Rooms>,
+    mut level_selection: ResMut<LevelSelection>,
+    level_query: Query<(&Transform, &Handle<LdtkLevel>)>,
+    player_query: Query<&GlobalTransform, With<Player>>,
+    ldtk_levels: Res<Assets<LdtkLevel>>,
+) {
+    if !rooms.is_active() {
+        return;
+    }
+
+    let mut current_rect = None;
+    let mut level_rects = Vec::new();
+    for (transform, handle) in &level_query {
+        let Some(ldtk_level) = ldtk_levels.get(handle) else {
+            continue;
+        };
+        let level = &ldtk_level.level;
+        if !rooms.rooms.contains(&level.iid) {
+            continue;
+        }
+        let min = transform.translation.truncate();
+        let rect = Rect::from_corners(
+            min,
+            min + Vec2::new(level.px_wid as f32, level.px_hei as f32),
+        );
+        if level_selection.is_match(&0, level) {
+            current_rect = Some(rect);
+        } else {
+            level_rects.push((level.iid.clone(), rect));
+        }
+    }
+    let Some(current_rect) = current_rect else {
+        return;
+    };
+
+    for player_transform in &player_query {
+        let position = player_transform.translation().truncate();
+        if current_rect.contains(position) {
+            continue;
+        }
+        if let Some((iid, _)) = level_rects.iter().find(|(_, rect)| rect.contains(position)) {
+            *level_selection = LevelSelection::Iid(iid.clone());
+            return;
+        }
+    }
+}
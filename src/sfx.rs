@@ -1,10 +1,11 @@
-use crate::state::GameState;
-use bevy::prelude::*;
+use crate::{loading_screen::LoadingAssets, state::GameState};
+use bevy::{audio::Volume, prelude::*, utils::HashMap};
 
 pub struct SfxPlugin;
 impl Plugin for SfxPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(OnEnter(GameState::Title), setup);
+        app.add_systems(OnEnter(GameState::Title), setup)
+            .add_systems(PostUpdate, play_requested_sfx);
     }
 }
 
@@ -16,9 +17,41 @@ pub struct SfxHandles {
     pub star: Handle<AudioSource>,
     pub select: Handle<AudioSource>,
     pub clear: Handle<AudioSource>,
+    pub teleport: Handle<AudioSource>,
 }
 
-fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
+/// How many copies of a sound may overlap, and how much its pitch may wander.
+#[derive(Clone, Copy, Debug)]
+pub struct SfxVoiceSetting {
+    pub max_voices: usize,
+    pub pitch_variation: f32,
+}
+
+impl Default for SfxVoiceSetting {
+    fn default() -> Self {
+        SfxVoiceSetting {
+            max_voices: 4,
+            pitch_variation: 0.0,
+        }
+    }
+}
+
+#[derive(Resource, Default)]
+pub struct SfxSettings {
+    pub voices: HashMap<Handle<AudioSource>, SfxVoiceSetting>,
+}
+
+impl SfxSettings {
+    fn get(&self, source: &Handle<AudioSource>) -> SfxVoiceSetting {
+        self.voices.get(source).copied().unwrap_or_default()
+    }
+}
+
+fn setup(
+    mut commands: Commands,
+    asset_server: Res<AssetServer>,
+    mut loading_assets: ResMut<LoadingAssets>,
+) {
     let handles = SfxHandles {
         jump: asset_server.load("sfx/jump.wav"),
         split: asset_server.load("sfx/split.wav"),
@@ -26,37 +59,98 @@ fn setup(mut commands: Commands, asset_server: Res<AssetServer>) {
         star: asset_server.load("sfx/star.wav"),
         select: asset_server.load("sfx/select.wav"),
         clear: asset_server.load("sfx/clear.wav"),
+        teleport: asset_server.load("sfx/teleport.wav"),
     };
 
+    for handle in [
+        &handles.jump,
+        &handles.split,
+        &handles.switch,
+        &handles.star,
+        &handles.select,
+        &handles.clear,
+        &handles.teleport,
+    ] {
+        loading_assets.add(handle);
+    }
+
+    let mut settings = SfxSettings::default();
+    settings.voices.insert(
+        handles.jump.clone(),
+        SfxVoiceSetting {
+            max_voices: 2,
+            pitch_variation: 0.05,
+        },
+    );
+    settings.voices.insert(
+        handles.split.clone(),
+        SfxVoiceSetting {
+            max_voices: 2,
+            pitch_variation: 0.0,
+        },
+    );
+    settings.voices.insert(
+        handles.star.clone(),
+        SfxVoiceSetting {
+            max_voices: 4,
+            pitch_variation: 0.15,
+        },
+    );
+
     commands.insert_resource(handles);
+    commands.insert_resource(settings);
+}
+
+/// A one-shot sound waiting to be checked against its polyphony limit.
+#[derive(Component)]
+struct SfxRequest(Handle<AudioSource>);
+
+/// A one-shot sound that is currently playing.
+#[derive(Component)]
+struct SfxVoice;
+
+fn play_requested_sfx(
+    mut commands: Commands,
+    request_query: Query<(Entity, &SfxRequest)>,
+    voice_query: Query<&Handle<AudioSource>, With<SfxVoice>>,
+    settings: Option<Res<SfxSettings>>,
+) {
+    let mut voices: HashMap<Handle<AudioSource>, usize> = HashMap::new();
+    for source in &voice_query {
+        *voices.entry(source.clone()).or_default() += 1;
+    }
+
+    for (e, SfxRequest(source)) in &request_query {
+        let setting = settings
+            .as_ref()
+            .map(|settings| settings.get(source))
+            .unwrap_or_default();
+        let playing = voices.entry(source.clone()).or_default();
+        if *playing >= setting.max_voices {
+            commands.entity(e).despawn();
+            continue;
+        }
+        *playing += 1;
 
-    let bgm = asset_server.load("music/Abstraction - Three Red Hearts - Puzzle Pieces.wav");
-    commands.play_bgm(bgm);
+        let speed = 1.0 + (fastrand::f32() * 2.0 - 1.0) * setting.pitch_variation;
+        commands.entity(e).remove::<SfxRequest>().insert((
+            AudioBundle {
+                source: source.clone(),
+                settings: PlaybackSettings::DESPAWN
+                    .with_volume(Volume::new_relative(0.2))
+                    .with_speed(speed),
+            },
+            SfxVoice,
+        ));
+    }
 }
 
 pub(crate) trait AudioControler {
     fn play_sfx(&mut self, handle: Handle<AudioSource>);
-    fn play_bgm(&mut self, handle: Handle<AudioSource>);
 }
 
 impl<'w, 's> AudioControler for Commands<'w, 's> {
     fn play_sfx(&mut self, source: Handle<AudioSource>) {
-        self.spawn(AudioBundle {
-            source,
-            settings: PlaybackSettings {
-                volume: bevy::audio::Volume::new_relative(0.2),
-                ..PlaybackSettings::ONCE
-            },
-        });
-    }
-
-    fn play_bgm(&mut self, source: Handle<AudioSource>) {
-        self.spawn(AudioBundle {
-            source,
-            settings: PlaybackSettings {
-                volume: bevy::audio::Volume::new_relative(0.12),
-                ..PlaybackSettings::LOOP
-            },
-        });
+        self.spawn(SfxRequest(source));
     }
 }
@@ -0,0 +1,66 @@
+use crate::components::*;
+use bevy::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+/// Levels above this share the last size group.
+const MAX_SIZE_GROUP_LEVEL: u8 = 7;
+
+pub struct SizeGatePlugin;
+
+impl Plugin for SizeGatePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (
+                group_players_by_size,
+                group_ground_sensors_by_size,
+                close_size_gates,
+            ),
+        );
+    }
+}
+
+/// Size gates sit in the first collision group; players of each level get a group of their own.
+fn size_group(level: u8) -> Group {
+    Group::from_bits_truncate(Group::GROUP_2.bits() << level.min(MAX_SIZE_GROUP_LEVEL))
+}
+
+fn group_players_by_size(
+    mut commands: Commands,
+    player_query: Query<(Entity, &Player), Added<Player>>,
+) {
+    for (e, player) in &player_query {
+        commands
+            .entity(e)
+            .insert(CollisionGroups::new(size_group(player.level), Group::ALL));
+    }
+}
+
+/// Ground sensors share their player's group, so a player never stands on a gate it passes through.
+fn group_ground_sensors_by_size(
+    mut commands: Commands,
+    sensor_query: Query<(Entity, &GroundSensor), Added<GroundSensor>>,
+    player_query: Query<&Player>,
+) {
+    for (e, sensor) in &sensor_query {
+        if let Ok(player) = player_query.get(sensor.ground_detection_entity) {
+            commands
+                .entity(e)
+                .insert(CollisionGroups::new(size_group(player.level), Group::ALL));
+        }
+    }
+}
+
+fn close_size_gates(
+    mut commands: Commands,
+    gate_query: Query<(Entity, &SizeGate), Added<SizeGate>>,
+) {
+    for (e, gate) in &gate_query {
+        let blocked = (0..=MAX_SIZE_GROUP_LEVEL)
+            .filter(|level| !(gate.min_level..=gate.max_level).contains(level))
+            .fold(Group::NONE, |groups, level| groups | size_group(level));
+        commands
+            .entity(e)
+            .insert(CollisionGroups::new(Group::GROUP_1, blocked));
+    }
+}
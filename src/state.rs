@@ -5,6 +5,7 @@ pub enum GameState {
     #[default]
     Title,
     Load,
+    LoadError,
     Spawn,
     Play,
     LevelClear,
@@ -1,6 +1,8 @@
 use crate::{
     animation::{Animation, Animations},
     components::*,
+    loading_screen::LoadingAssets,
+    logic::evaluate_signals,
     sfx::{AudioControler, SfxHandles},
     state::GameState,
 };
@@ -11,16 +13,35 @@ pub struct SwitchPlugin;
 
 impl Plugin for SwitchPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(OnEnter(GameState::Title), store_map_texture_handle);
+        app.add_event::<SwitchPushed>()
+            .add_systems(OnEnter(GameState::Title), store_map_texture_handle);
 
-        app.add_systems(Update, add_switch_pieds);
         app.add_systems(
             Update,
-            (send_switch_pushed_event, switch_pushed_motion).run_if(in_state(GameState::Play)),
+            (add_switch_pieds, store_unpushed_sprite, lay_door_tracks),
         );
         app.add_systems(
             Update,
-            (open_door, remove_door).run_if(in_state(GameState::Play)),
+            (
+                weigh_switches,
+                start_switch_countdown,
+                tick_switch_countdown,
+                switch_pushed_motion,
+                switch_released_motion,
+            )
+                .run_if(in_state(GameState::Play)),
+        );
+        app.add_systems(
+            Update,
+            (
+                evaluate_signals,
+                update_doors,
+                hide_opened_door,
+                close_door,
+                slide_doors,
+            )
+                .chain()
+                .run_if(in_state(GameState::Play)),
         );
     }
 }
@@ -34,8 +55,10 @@ fn store_map_texture_handle(
     mut commands: Commands,
     asset_server: Res<AssetServer>,
     mut texture_atlases: ResMut<Assets<TextureAtlas>>,
+    mut loading_assets: ResMut<LoadingAssets>,
 ) {
     let texture_handle = asset_server.load("atlas/player.png");
+    loading_assets.add(&texture_handle);
     let texture_atlas =
         TextureAtlas::from_grid(texture_handle, (32.0, 32.0).into(), 6, 5, None, None);
     let texture_atlas_handle = texture_atlases.add(texture_atlas);
@@ -68,26 +91,155 @@ fn add_switch_pieds(
     }
 }
 
+/// Sent when a player pushes a switch, unlike `Pushed` being restored by checkpoints and rooms.
 #[derive(Event)]
 struct SwitchPushed(Entity);
 
 #[derive(Component)]
-struct Pushed;
+pub(crate) struct Pushed;
 
+/// Switches are pushed once enough weight stands on them; pressure plates pop back up when it leaves.
 #[allow(clippy::type_complexity)]
-fn send_switch_pushed_event(
+fn weigh_switches(
     mut commands: Commands,
-    switch_query: Query<Entity, (With<Switch>, Without<Player>, Without<Pushed>)>,
+    mut switch_query: Query<(
+        Entity,
+        &mut SwitchPressers,
+        &RequiredWeight,
+        Option<&PressurePlate>,
+        Option<&Pushed>,
+    )>,
+    player_query: Query<&Player>,
     mut collisions: EventReader<CollisionEvent>,
+    mut pushed_events: EventWriter<SwitchPushed>,
 ) {
     for collision_event in collisions.iter() {
-        if let CollisionEvent::Started(e1, e2, _) = collision_event {
-            if switch_query.contains(*e1) {
-                commands.entity(*e1).insert(Pushed);
-            } else if switch_query.contains(*e2) {
-                commands.entity(*e2).insert(Pushed);
+        let (e1, e2, started) = match collision_event {
+            CollisionEvent::Started(e1, e2, _) => (e1, e2, true),
+            CollisionEvent::Stopped(e1, e2, _) => (e1, e2, false),
+        };
+        let (switch, player) = if switch_query.contains(*e1) {
+            (*e1, *e2)
+        } else if switch_query.contains(*e2) {
+            (*e2, *e1)
+        } else {
+            continue;
+        };
+        if !player_query.contains(player) {
+            continue;
+        }
+        let Ok((_, mut pressers, ..)) = switch_query.get_mut(switch) else {
+            continue;
+        };
+        if started {
+            pressers.0.insert(player);
+        } else {
+            pressers.0.remove(&player);
+        }
+    }
+
+    for (switch, mut pressers, required_weight, pressure_plate, pushed) in &mut switch_query {
+        // players consumed by stars or split leave without a stop event
+        pressers.0.retain(|e| player_query.contains(*e));
+        let weight: f32 = player_query
+            .iter_many(pressers.0.iter())
+            .map(Player::weight)
+            .sum();
+        let heavy_enough = !pressers.0.is_empty() && weight >= required_weight.0 - f32::EPSILON;
+        match (heavy_enough, pushed.is_some()) {
+            (true, false) => {
+                commands.entity(switch).insert(Pushed);
+                pushed_events.send(SwitchPushed(switch));
+            }
+            (false, true) if pressure_plate.is_some() => {
+                commands.entity(switch).remove::<Pushed>();
+            }
+            _ => (),
+        }
+    }
+}
+
+#[derive(Component)]
+struct SwitchCountdown(Timer);
+
+#[derive(Component)]
+struct CountdownText;
+
+fn start_switch_countdown(
+    mut commands: Commands,
+    mut pushed_events: EventReader<SwitchPushed>,
+    switch_query: Query<&SwitchDuration>,
+    asset_server: Res<AssetServer>,
+) {
+    for &SwitchPushed(e) in pushed_events.iter() {
+        let Ok(duration) = switch_query.get(e) else {
+            continue;
+        };
+        commands
+            .entity(e)
+            .insert(SwitchCountdown(Timer::from_seconds(
+                duration.0,
+                TimerMode::Once,
+            )))
+            .with_children(|switch| {
+                switch.spawn((
+                    Text2dBundle {
+                        text: Text::from_section(
+                            format!("{}", duration.0.ceil()),
+                            TextStyle {
+                                font: asset_server.load("fonts/PeaberryMono.ttf"),
+                                font_size: 24.0,
+                                color: Color::WHITE,
+                            },
+                        ),
+                        transform: Transform::from_translation(Vec3::new(0.0, 16.0, 6.0))
+                            .with_scale(Vec3::splat(0.5)),
+                        ..Default::default()
+                    },
+                    CountdownText,
+                ));
+            });
+    }
+}
+
+/// Timed switches pop back up when their countdown runs out, which closes their doors.
+fn tick_switch_countdown(
+    mut commands: Commands,
+    mut switch_query: Query<(Entity, &mut SwitchCountdown, &mut SwitchPressers, &Children)>,
+    mut text_query: Query<&mut Text, With<CountdownText>>,
+    time: Res<Time>,
+) {
+    for (e, mut countdown, mut pressers, children) in &mut switch_query {
+        countdown.0.tick(time.delta());
+        let mut texts = text_query.iter_many_mut(children);
+        while let Some(mut text) = texts.fetch_next() {
+            text.sections[0].value = format!("{}", countdown.0.remaining_secs().ceil());
+        }
+        if !countdown.0.finished() {
+            continue;
+        }
+        for &child in children {
+            if text_query.contains(child) {
+                commands.entity(child).despawn_recursive();
             }
         }
+        // whoever is still standing on it has to step off and on again
+        pressers.0.clear();
+        commands.entity(e).remove::<(SwitchCountdown, Pushed)>();
+    }
+}
+
+/// The sprite a switch shows before it is pushed, so a released pressure plate can go back to it.
+#[derive(Component)]
+struct UnpushedSprite(usize);
+
+#[allow(clippy::type_complexity)]
+fn store_unpushed_sprite(
+    mut commands: Commands,
+    switch_query: Query<(Entity, &TextureAtlasSprite), (With<Switch>, Without<UnpushedSprite>)>,
+) {
+    for (e, sprite) in &switch_query {
+        commands.entity(e).insert(UnpushedSprite(sprite.index));
     }
 }
 
@@ -100,33 +252,102 @@ fn switch_pushed_motion(
     }
 }
 
+fn switch_released_motion(
+    mut released: RemovedComponents<Pushed>,
+    mut switch_query: Query<(&mut TextureAtlasSprite, &UnpushedSprite), With<Switch>>,
+) {
+    for e in released.iter() {
+        if let Ok((mut texture_sprite, unpushed)) = switch_query.get_mut(e) {
+            texture_sprite.index = unpushed.0;
+        }
+    }
+}
+
+#[derive(Component)]
+pub(crate) struct Open;
+
+/// On a door put back by a checkpoint, so settling into its restored state plays no sound or
+/// animation.
 #[derive(Component)]
-struct Open;
+pub(crate) struct Restored;
 
-fn open_door(
+/// Opens and closes every wired door to match the signals of the switches and gates driving it.
+#[allow(clippy::type_complexity)]
+fn update_doors(
     mut commands: Commands,
-    door_query: Query<(Entity, &Door), Without<Open>>,
-    pushed_switch_query: Query<&RelatedDoor, (With<Switch>, Added<Pushed>)>,
+    door_query: Query<(
+        Entity,
+        &Door,
+        Option<&SwitchLogic>,
+        Option<&Open>,
+        Option<&Restored>,
+    )>,
+    source_query: Query<(&RelatedDoors, &Signal)>,
     sfxs: Res<SfxHandles>,
 ) {
-    for related_door in pushed_switch_query.iter() {
-        for (door_entity, door) in door_query.iter() {
-            if door.0 == related_door.0 {
+    for (door_entity, door, switch_logic, open, restored) in door_query.iter() {
+        if restored.is_some() {
+            commands.entity(door_entity).remove::<Restored>();
+        }
+        let linked = source_query
+            .iter()
+            .filter(|(related_doors, _)| related_doors.0.contains(&door.0));
+        let (linked_count, on_count) = linked.fold((0, 0), |(l, o), (_, signal)| {
+            (l + 1, o + usize::from(signal.0))
+        });
+        // doors nobody wired a switch to, such as locks, are left alone
+        if linked_count == 0 {
+            continue;
+        }
+        let should_open = match switch_logic.copied().unwrap_or_default() {
+            SwitchLogic::Any => on_count > 0,
+            SwitchLogic::All => on_count == linked_count,
+        };
+        match (should_open, open.is_some()) {
+            (true, false) => {
                 commands.entity(door_entity).insert(Open);
-                commands.play_sfx(sfxs.switch.clone());
             }
+            (false, true) => {
+                commands.entity(door_entity).remove::<Open>();
+            }
+            _ => continue,
+        }
+        if restored.is_none() {
+            commands.play_sfx(sfxs.switch.clone());
         }
     }
 }
 
+/// The collider of an open door, put back when it closes.
+#[derive(Component)]
+struct OpenDoorCollider(Collider);
+
 #[allow(clippy::type_complexity)]
-fn remove_door(
+fn hide_opened_door(
     mut commands: Commands,
-    opened_door_query: Query<(Entity, &Transform), (With<Door>, Added<Open>)>,
+    opened_door_query: Query<
+        (
+            Entity,
+            &GlobalTransform,
+            &Collider,
+            Option<&DoorMotion>,
+            Option<&Restored>,
+        ),
+        (With<Door>, Added<Open>),
+    >,
     animations: Res<Animations>,
 ) {
-    for (e, transform) in opened_door_query.iter() {
-        commands.entity(e).despawn();
+    for (e, transform, collider, motion, restored) in opened_door_query.iter() {
+        if matches!(motion, Some(DoorMotion::Slide { .. })) {
+            continue;
+        }
+        commands
+            .entity(e)
+            .remove::<Collider>()
+            .insert((OpenDoorCollider(collider.clone()), Visibility::Hidden));
+        if restored.is_some() {
+            continue;
+        }
 
         let (texture_atlas, sprite, animation_setting) = animations
             .animations
@@ -137,7 +358,7 @@ fn remove_door(
         commands.spawn((
             SpriteSheetBundle {
                 texture_atlas,
-                transform: *transform,
+                transform: transform.compute_transform(),
                 sprite,
                 ..Default::default()
             },
@@ -145,3 +366,154 @@ fn remove_door(
         ));
     }
 }
+
+/// Closing doors push players out towards the nearer side, or crush them if that side is blocked.
+#[allow(clippy::type_complexity)]
+fn close_door(
+    mut commands: Commands,
+    mut closed: RemovedComponents<Open>,
+    door_query: Query<(&GlobalTransform, &OpenDoorCollider), With<Door>>,
+    mut player_query: Query<(&GlobalTransform, &mut Transform, &Collider), With<Player>>,
+    rapier_context: Res<RapierContext>,
+    animations: Res<Animations>,
+) {
+    for door_entity in closed.iter() {
+        let Ok((door_transform, OpenDoorCollider(door_collider))) = door_query.get(door_entity)
+        else {
+            continue;
+        };
+        commands
+            .entity(door_entity)
+            .insert((door_collider.clone(), Visibility::Inherited))
+            .remove::<OpenDoorCollider>();
+
+        let Some(door_half_extents) = door_collider.as_cuboid().map(|c| c.half_extents()) else {
+            continue;
+        };
+        let door_center = door_transform.translation().truncate();
+
+        let mut trapped = Vec::new();
+        rapier_context.intersections_with_shape(
+            door_center,
+            0.,
+            door_collider,
+            QueryFilter::only_dynamic(),
+            |e| {
+                trapped.push(e);
+                true
+            },
+        );
+
+        for e in trapped {
+            let Ok((player_global, mut player_transform, player_collider)) =
+                player_query.get_mut(e)
+            else {
+                continue;
+            };
+            let player_half_extents = player_collider
+                .as_cuboid()
+                .map_or(Vec2::ZERO, |c| c.half_extents());
+            let player_center = player_global.translation().truncate();
+            let offset = player_center - door_center;
+
+            // push along the door's thin axis, which is the shortest way out
+            let target = if door_half_extents.x < door_half_extents.y {
+                Vec2::new(
+                    door_center.x
+                        + offset.x.signum() * (door_half_extents.x + player_half_extents.x + 1.),
+                    player_center.y,
+                )
+            } else {
+                Vec2::new(
+                    player_center.x,
+                    door_center.y
+                        + offset.y.signum() * (door_half_extents.y + player_half_extents.y + 1.),
+                )
+            };
+
+            let blocked = rapier_context
+                .intersection_with_shape(
+                    target,
+                    0.,
+                    player_collider,
+                    QueryFilter::only_fixed().exclude_collider(door_entity),
+                )
+                .is_some();
+            if blocked {
+                commands.entity(e).despawn_recursive();
+
+                let (texture_atlas, sprite, animation_setting) = animations
+                    .animations
+                    .get(&Animation::DespawnStar)
+                    .expect("Star animation not found")
+                    .clone();
+                commands.spawn((
+                    SpriteSheetBundle {
+                        texture_atlas,
+                        transform: player_global.compute_transform(),
+                        sprite,
+                        ..Default::default()
+                    },
+                    animation_setting,
+                ));
+            } else {
+                player_transform.translation += (target - player_center).extend(0.);
+            }
+        }
+    }
+}
+
+/// Where a sliding door sits when closed.
+#[derive(Component)]
+pub(crate) struct DoorTrack {
+    closed: Vec3,
+    /// How far it slid this frame, so the players riding it as a lift can follow.
+    pub(crate) step: Vec3,
+}
+
+fn lay_door_tracks(
+    mut commands: Commands,
+    door_query: Query<(Entity, &Transform, &DoorMotion), Added<DoorMotion>>,
+) {
+    for (e, transform, motion) in &door_query {
+        if let DoorMotion::Slide { .. } = motion {
+            commands.entity(e).insert((
+                DoorTrack {
+                    closed: transform.translation,
+                    step: Vec3::ZERO,
+                },
+                RigidBody::KinematicPositionBased,
+            ));
+        }
+    }
+}
+
+pub(crate) fn slide_doors(
+    mut door_query: Query<(&mut Transform, &DoorMotion, &mut DoorTrack, Option<&Open>)>,
+    time: Res<Time>,
+) {
+    for (mut transform, motion, mut track, open) in &mut door_query {
+        track.step = Vec3::ZERO;
+
+        let DoorMotion::Slide { offset, speed } = *motion else {
+            continue;
+        };
+        let target = if open.is_some() {
+            track.closed + offset.extend(0.)
+        } else {
+            track.closed
+        };
+        let remaining = target - transform.translation;
+        if remaining == Vec3::ZERO {
+            continue;
+        }
+        let start = transform.translation;
+        let distance = speed * time.delta_seconds();
+        if remaining.length() <= distance {
+            transform.translation = target;
+        } else {
+            transform.translation += remaining.normalize() * distance;
+        }
+        track.step = transform.translation - start;
+    }
+}
@@ -0,0 +1,112 @@
+use crate::{
+    animation::{Animation, Animations},
+    components::*,
+    sfx::{AudioControler, SfxHandles},
+    state::GameState,
+};
+use bevy::{prelude::*, utils::HashSet};
+use bevy_ecs_ldtk::prelude::*;
+use bevy_rapier2d::prelude::*;
+
+/// How long a player who just teleported ignores teleporters, so it isn't sent straight back.
+const TELEPORT_COOLDOWN_SECONDS: f32 = 0.5;
+
+pub struct TeleporterPlugin;
+
+impl Plugin for TeleporterPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            (tick_teleport_cooldown, teleport_players)
+                .chain()
+                .run_if(in_state(GameState::Play)),
+        );
+    }
+}
+
+#[derive(Component)]
+struct TeleportCooldown(Timer);
+
+fn tick_teleport_cooldown(
+    mut commands: Commands,
+    mut cooldown_query: Query<(Entity, &mut TeleportCooldown)>,
+    time: Res<Time>,
+) {
+    for (e, mut cooldown) in &mut cooldown_query {
+        if cooldown.0.tick(time.delta()).just_finished() {
+            commands.entity(e).remove::<TeleportCooldown>();
+        }
+    }
+}
+
+/// Only the transform moves, so the player keeps its velocity through the jump.
+#[allow(clippy::type_complexity)]
+fn teleport_players(
+    mut commands: Commands,
+    mut collisions: EventReader<CollisionEvent>,
+    teleporter_query: Query<(&EntityIid, &GlobalTransform, &Teleporter)>,
+    mut player_query: Query<
+        (&mut Transform, &GlobalTransform),
+        (With<Player>, Without<TeleportCooldown>),
+    >,
+    sfxs: Res<SfxHandles>,
+    animation: Res<Animations>,
+) {
+    let mut teleported = HashSet::new();
+    for collision_event in collisions.iter() {
+        let CollisionEvent::Started(e1, e2, _) = collision_event else {
+            continue;
+        };
+        let (teleporter, player) = match (teleporter_query.get(*e1), teleporter_query.get(*e2)) {
+            (Ok((_, _, teleporter)), _) => (teleporter, *e2),
+            (_, Ok((_, _, teleporter))) => (teleporter, *e1),
+            _ => continue,
+        };
+        if teleported.contains(&player) {
+            continue;
+        }
+        let Some(destination) = teleporter.destination.as_ref().and_then(|iid| {
+            teleporter_query
+                .iter()
+                .find(|(other, _, _)| other.as_str() == iid)
+                .map(|(_, transform, _)| transform.translation())
+        }) else {
+            continue;
+        };
+        let Ok((mut transform, global_transform)) = player_query.get_mut(player) else {
+            continue;
+        };
+
+        let departure = global_transform.translation();
+        // players may hang off the level or the world entity, so move by the world-space offset
+        transform.translation += (destination - departure).truncate().extend(0.);
+        commands
+            .entity(player)
+            .insert(TeleportCooldown(Timer::from_seconds(
+                TELEPORT_COOLDOWN_SECONDS,
+                TimerMode::Once,
+            )));
+        teleported.insert(player);
+
+        let Some((texture_atlas, sprite, animation_setting)) =
+            animation.animations.get(&Animation::DespawnStar)
+        else {
+            continue;
+        };
+        for position in [departure, destination] {
+            commands.spawn((
+                SpriteSheetBundle {
+                    texture_atlas: texture_atlas.clone(),
+                    sprite: sprite.clone(),
+                    transform: Transform::from_translation(position),
+                    ..Default::default()
+                },
+                animation_setting.clone(),
+            ));
+        }
+    }
+
+    if !teleported.is_empty() {
+        commands.play_sfx(sfxs.teleport.clone());
+    }
+}